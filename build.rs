@@ -1,7 +1,13 @@
+use std::{env, path::PathBuf};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Descriptor set for gRPC server reflection
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
+        .file_descriptor_set_path(out_dir.join("mcp_descriptor.bin"))
         .compile(
             &["proto/mcp.proto"],
             &["proto"],
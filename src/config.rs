@@ -1,5 +1,6 @@
 // MCP Service Configuration
-use anyhow::Result;
+use crate::search::ToolNaming;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,10 @@ pub struct McpConfig {
     pub service_port: u16,
     pub host: String,
     
+    // gRPC API for internal callers (client-connector)
+    pub grpc_host: String,
+    pub grpc_port: u16,
+    
     // Search and retrieval service URLs
     pub embeddings_service_url: Option<String>,
     pub relation_graph_url: Option<String>,
@@ -21,8 +26,27 @@ pub struct McpConfig {
     pub request_timeout_secs: u64,
     pub cache_ttl_secs: u64,
     
+    // How often subscribed resources are polled for changes
+    pub resource_poll_interval_secs: u64,
+    
+    // Streamable HTTP sessions with no stream or request for this long are closed
+    pub session_ttl_secs: u64,
+    
+    // Browser origins allowed on the HTTP and WebSocket transports ("*" allows any).
+    // Requests without an Origin header (non-browser clients) are always accepted.
+    pub allowed_origins: Vec<String>,
+    
+    // Tool name form for all clients; detected per client from clientInfo when unset
+    pub tool_naming: Option<ToolNaming>,
+    
     // Rate limiting
     pub rate_limit_per_minute: u32,
+    
+    // Logging (levels come from RUST_LOG). Logs go to stderr unless a directory is
+    // set, since stdout carries the stdio MCP transport.
+    pub log_json: bool,
+    pub log_dir: Option<String>,
+    pub log_rotation: String,
 }
 
 impl McpConfig {
@@ -33,6 +57,11 @@ impl McpConfig {
                 .parse()?,
             host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
             
+            grpc_host: std::env::var("GRPC_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            grpc_port: std::env::var("GRPC_PORT")
+                .unwrap_or_else(|_| "50056".to_string())
+                .parse()?,
+            
             // Search and retrieval services
             embeddings_service_url: std::env::var("EMBEDDINGS_SERVICE_GRPC_ADDR").ok(),
             relation_graph_url: std::env::var("RELATION_GRAPH_GRPC_ADDR").ok(),
@@ -50,10 +79,45 @@ impl McpConfig {
             cache_ttl_secs: std::env::var("CACHE_TTL_SECS")
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()?,
+            resource_poll_interval_secs: std::env::var("RESOURCE_POLL_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()?,
+            session_ttl_secs: std::env::var("MCP_SESSION_TTL_SECS")
+                .unwrap_or_else(|_| "1800".to_string())
+                .parse()?,
+            allowed_origins: std::env::var("MCP_ALLOWED_ORIGINS")
+                .map(|origins| {
+                    origins.split(',')
+                        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                        .filter(|origin| !origin.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            tool_naming: match std::env::var("MCP_TOOL_NAMING") {
+                Ok(naming) if naming != "auto" => Some(
+                    ToolNaming::parse(&naming)
+                        .ok_or_else(|| anyhow!("MCP_TOOL_NAMING must be auto, dotted or underscore, got '{}'", naming))?
+                ),
+                _ => None,
+            },
             
             rate_limit_per_minute: std::env::var("RATE_LIMIT_PER_MINUTE")
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()?,
+            
+            log_json: match std::env::var("LOG_FORMAT").as_deref() {
+                Ok("json") => true,
+                Ok("text") | Err(_) => false,
+                Ok(other) => return Err(anyhow!("LOG_FORMAT must be text or json, got '{}'", other)),
+            },
+            log_dir: std::env::var("LOG_DIR").ok(),
+            log_rotation: std::env::var("LOG_ROTATION").unwrap_or_else(|_| "daily".to_string()),
         })
     }
+    
+    /// Whether a request carrying this `Origin` header may use the MCP transports
+    pub fn origin_allowed(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.allowed_origins.iter().any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(origin))
+    }
 }
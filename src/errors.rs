@@ -6,12 +6,30 @@ pub type McpResult<T> = Result<T, McpError>;
 
 #[derive(Debug, Error)]
 pub enum McpError {
+    #[error("Parse error: {0}")]
+    ParseError(String),
+    
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    
+    #[error("Unsupported protocol version: {requested}")]
+    UnsupportedProtocolVersion {
+        requested: String,
+        supported: Vec<String>,
+    },
+    
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
     
     #[error("Invalid arguments: {0}")]
     InvalidArguments(String),
     
+    #[error("Invalid arguments for {tool}: {}", violations.join("; "))]
+    ArgumentViolations {
+        tool: String,
+        violations: Vec<String>,
+    },
+    
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
     
@@ -48,8 +66,45 @@ pub struct McpErrorResponse {
 }
 
 impl McpError {
+    /// Errors in the request itself, as opposed to failures while executing it.
+    ///
+    /// Protocol errors are reported as JSON-RPC errors; execution failures of a
+    /// tool are returned to the model as `isError` tool results instead.
+    pub fn is_protocol_error(&self) -> bool {
+        matches!(
+            self,
+            McpError::ParseError(_)
+                | McpError::InvalidRequest(_)
+                | McpError::UnsupportedProtocolVersion { .. }
+                | McpError::ToolNotFound(_)
+                | McpError::InvalidArguments(_)
+                | McpError::ArgumentViolations { .. }
+        )
+    }
+    
     pub fn to_jsonrpc_error(&self) -> McpErrorResponse {
         match self {
+            McpError::ParseError(msg) => McpErrorResponse {
+                code: -32700,
+                message: msg.clone(),
+                data: None,
+            },
+            McpError::InvalidRequest(msg) => McpErrorResponse {
+                code: -32600,
+                message: msg.clone(),
+                data: None,
+            },
+            McpError::UnsupportedProtocolVersion { requested, supported } => McpErrorResponse {
+                code: -32602,
+                message: format!(
+                    "Unsupported protocol version {}; this server supports {}",
+                    requested, supported.join(", ")
+                ),
+                data: Some(serde_json::json!({
+                    "requested": requested,
+                    "supported": supported
+                })),
+            },
             McpError::ToolNotFound(msg) => McpErrorResponse {
                 code: -32601,
                 message: msg.clone(),
@@ -60,6 +115,11 @@ impl McpError {
                 message: msg.clone(),
                 data: None,
             },
+            McpError::ArgumentViolations { violations, .. } => McpErrorResponse {
+                code: -32602,
+                message: self.to_string(),
+                data: Some(serde_json::json!({ "violations": violations })),
+            },
             McpError::Unauthorized(msg) => McpErrorResponse {
                 code: -32001,
                 message: msg.clone(),
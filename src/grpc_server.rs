@@ -1,32 +1,137 @@
 //! MCP Server gRPC Server
-//! Handles gRPC requests for MCP tool operations
+//! Serves the `confuse.mcp.v1.Mcp` service from proto/mcp.proto so internal
+//! callers (the client-connector) can list and call tools without speaking MCP.
+//! Every RPC is routed into the same `SearchManager` the MCP transports use.
+//! The standard `grpc.health.v1.Health` service (with one entry per backend)
+//! and server reflection are served alongside it.
 
-use std::sync::Arc;
-use tonic::{transport::Server, Request, Response, Status};
 use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::{stream, Stream, StreamExt};
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::AbortHandle;
+use tonic::{transport::{server::Router, Server}, Request, Response, Status};
+use tonic_health::{server::HealthReporter, ServingStatus};
+use tracing::{info, warn, Instrument};
 
-use crate::core::McpService;
-use crate::core::Config;
+use crate::{
+    config::McpConfig,
+    errors::{McpError, McpResult},
+    mcp::{McpTool, ToolContext, ToolEvent},
+    search::SearchManager,
+};
 use crate::proto::confuse::mcp::v1::{
-    mcp_server::Mcp,
+    FILE_DESCRIPTOR_SET,
+    mcp_server::{Mcp, McpServer},
+    tool_event,
     ListToolsRequest, ListToolsResponse, CallToolRequest, CallToolResponse,
-    ToolSchemaRequest, ToolSchema, Tool,
+    CallToolV2Request, CallToolV2Response, ToolSchemaRequest, ToolSchema, Tool,
+    ToolEvent as ProtoToolEvent, ToolPartialResult, ToolProgress,
+    ListResourcesRequest, ListResourcesResponse, ListResourceTemplatesRequest,
+    ListResourceTemplatesResponse, ReadResourceRequest, ReadResourceResponse,
+    Resource, ResourceTemplate,
 };
 
+/// How often backend health is re-checked for `grpc.health.v1`
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+type ToolEventStream = Pin<Box<dyn Stream<Item = Result<ProtoToolEvent, Status>> + Send>>;
+
 pub struct McpGrpcService {
-    service: Arc<McpService>,
-    config: Arc<Config>,
+    search_manager: Arc<SearchManager>,
 }
 
 impl McpGrpcService {
-    pub fn new(service: Arc<McpService>, config: Arc<Config>) -> Self {
-        Self {
-            service,
-            config,
+    pub fn new(search_manager: Arc<SearchManager>) -> Self {
+        Self { search_manager }
+    }
+}
+
+/// Aborts a spawned task when dropped, e.g. along with the response stream
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run a tool for any CallTool version. Request errors (unknown tool, invalid
+/// arguments) become a `Status`; a tool that fails while running is reported
+/// in the response, like an MCP `isError` result.
+async fn run_tool(
+    search_manager: &SearchManager,
+    tool_id: &str,
+    arguments: Value,
+    ctx: &ToolContext,
+) -> Result<ToolOutcome, Status> {
+    let started = Instant::now();
+    let result = search_manager.call_tool(tool_id, arguments, ctx).await;
+
+    let mut metadata = HashMap::new();
+    metadata.insert("duration_ms".to_string(), started.elapsed().as_millis().to_string());
+
+    let result = match result {
+        Ok(value) => Ok(value),
+        Err(e) if !e.is_protocol_error() => {
+            warn!("Tool {} failed: {}", tool_id, e);
+            Err(format!("Tool {} failed: {}", tool_id, e))
+        }
+        Err(e) => return Err(to_status(e)),
+    };
+
+    Ok(ToolOutcome { result, metadata })
+}
+
+struct ToolOutcome {
+    result: Result<Value, String>,
+    metadata: HashMap<String, String>,
+}
+
+impl From<ToolOutcome> for CallToolV2Response {
+    fn from(outcome: ToolOutcome) -> Self {
+        match outcome.result {
+            Ok(value) => CallToolV2Response {
+                success: true,
+                result_json: value.to_string(),
+                error: None,
+                metadata: outcome.metadata,
+            },
+            Err(error) => CallToolV2Response {
+                success: false,
+                result_json: String::new(),
+                error: Some(error),
+                metadata: outcome.metadata,
+            },
         }
     }
 }
 
+/// Parse `arguments_json`, where an empty string means no arguments
+fn parse_arguments_json(arguments_json: &str) -> McpResult<Value> {
+    if arguments_json.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    serde_json::from_str(arguments_json)
+        .map_err(|e| McpError::InvalidArguments(format!("arguments_json is not valid JSON: {}", e)))
+}
+
+fn to_proto_event(event: ToolEvent) -> ProtoToolEvent {
+    let event = match event {
+        ToolEvent::Progress { progress, total, message } => {
+            tool_event::Event::Progress(ToolProgress { progress, total, message })
+        }
+        ToolEvent::Partial { stage, result } => tool_event::Event::Partial(ToolPartialResult {
+            stage,
+            result_json: result.to_string(),
+        }),
+    };
+    ProtoToolEvent { event: Some(event) }
+}
+
 #[tonic::async_trait]
 impl Mcp for McpGrpcService {
     async fn list_tools(
@@ -34,28 +139,17 @@ impl Mcp for McpGrpcService {
         request: Request<ListToolsRequest>,
     ) -> Result<Response<ListToolsResponse>, Status> {
         let req = request.into_inner();
-        
-        tracing::info!("Listing tools for category: {:?}", req.category);
-        
-        match self.service.list_tools(req.category).await {
-            Ok(tools) => {
-                let proto_tools: Vec<Tool> = tools.into_iter().map(|t| Tool {
-                    tool_id: t.tool_id,
-                    name: t.name,
-                    description: t.description,
-                    category: t.category,
-                    parameters_schema: t.parameters_schema,
-                }).collect();
-
-                Ok(Response::new(ListToolsResponse {
-                    tools: proto_tools,
-                }))
-            }
-            Err(e) => {
-                tracing::error!("Failed to list tools: {}", e);
-                Err(Status::internal(format!("Tool listing failed: {}", e)))
-            }
-        }
+
+        tracing::debug!("Listing tools for category: {:?}", req.category);
+
+        let mut tools: Vec<Tool> = self.search_manager.list_all_tools()
+            .into_iter()
+            .map(to_proto_tool)
+            .filter(|t| req.category.as_ref().is_none_or(|c| &t.category == c))
+            .collect();
+        tools.sort_by(|a, b| a.tool_id.cmp(&b.tool_id));
+
+        Ok(Response::new(ListToolsResponse { tools }))
     }
 
     async fn call_tool(
@@ -63,23 +157,86 @@ impl Mcp for McpGrpcService {
         request: Request<CallToolRequest>,
     ) -> Result<Response<CallToolResponse>, Status> {
         let req = request.into_inner();
-        
-        tracing::info!("Calling tool: {} for user: {}", req.tool_id, req.user_id);
-        
-        match self.service.call_tool(&req.tool_id, req.parameters, &req.user_id, &req.session_id).await {
-            Ok(result) => {
-                Ok(Response::new(CallToolResponse {
-                    success: result.success,
-                    result: result.result,
-                    error: result.error,
-                    metadata: result.metadata,
-                }))
-            }
-            Err(e) => {
-                tracing::error!("Failed to call tool {}: {}", req.tool_id, e);
-                Err(Status::internal(format!("Tool call failed: {}", e)))
+
+        info!("🔧 [gRPC] Calling tool: {} for user: {} (session {})", req.tool_id, req.user_id, req.session_id);
+
+        let tool = self.search_manager.get_tool(&req.tool_id).map_err(to_status)?;
+        let arguments = coerce_parameters(tool.input_schema.as_ref(), req.parameters);
+        let outcome = run_tool(&self.search_manager, &req.tool_id, arguments, &ToolContext::detached()).await?;
+
+        Ok(Response::new(match outcome.result {
+            Ok(value) => CallToolResponse {
+                success: true,
+                result: value.to_string(),
+                error: None,
+                metadata: outcome.metadata,
+            },
+            Err(error) => CallToolResponse {
+                success: false,
+                result: String::new(),
+                error: Some(error),
+                metadata: outcome.metadata,
+            },
+        }))
+    }
+
+    async fn call_tool_v2(
+        &self,
+        request: Request<CallToolV2Request>,
+    ) -> Result<Response<CallToolV2Response>, Status> {
+        let req = request.into_inner();
+
+        info!("🔧 [gRPC] Calling tool: {} for user: {} (session {})", req.tool_id, req.user_id, req.session_id);
+
+        let arguments = parse_arguments_json(&req.arguments_json).map_err(to_status)?;
+        let outcome = run_tool(&self.search_manager, &req.tool_id, arguments, &ToolContext::detached()).await?;
+
+        Ok(Response::new(outcome.into()))
+    }
+
+    type CallToolStreamStream = ToolEventStream;
+
+    async fn call_tool_stream(
+        &self,
+        request: Request<CallToolV2Request>,
+    ) -> Result<Response<Self::CallToolStreamStream>, Status> {
+        let req = request.into_inner();
+
+        info!("🔧 [gRPC] Streaming tool: {} for user: {} (session {})", req.tool_id, req.user_id, req.session_id);
+
+        // Reject unknown tools and malformed arguments before opening the stream
+        self.search_manager.get_tool(&req.tool_id).map_err(to_status)?;
+        let arguments = parse_arguments_json(&req.arguments_json).map_err(to_status)?;
+
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let (result_tx, result_rx) = oneshot::channel();
+        let search_manager = self.search_manager.clone();
+        let task = tokio::spawn(
+            async move {
+                // Dropping the context at the end closes the event stream
+                let ctx = ToolContext::streaming(events_tx);
+                let outcome = run_tool(&search_manager, &req.tool_id, arguments, &ctx).await;
+                let _ = result_tx.send(outcome);
             }
-        }
+            .in_current_span(),
+        );
+        // Stop the tool if the client cancels or disconnects before the result
+        let task = AbortOnDrop(task.abort_handle());
+
+        let events = stream::unfold(events_rx, |mut rx| async move {
+            rx.recv().await.map(|event| (Ok(to_proto_event(event)), rx))
+        });
+        let result = stream::once(async move {
+            let _task = task;
+            let outcome = result_rx
+                .await
+                .map_err(|_| Status::internal("Tool call ended without a result"))??;
+            Ok(ProtoToolEvent {
+                event: Some(tool_event::Event::Result(outcome.into())),
+            })
+        });
+
+        Ok(Response::new(Box::pin(events.chain(result))))
     }
 
     async fn get_tool_schema(
@@ -87,40 +244,306 @@ impl Mcp for McpGrpcService {
         request: Request<ToolSchemaRequest>,
     ) -> Result<Response<ToolSchema>, Status> {
         let req = request.into_inner();
-        
-        tracing::info!("Getting schema for tool: {}", req.tool_id);
-        
-        match self.service.get_tool_schema(&req.tool_id).await {
-            Ok(schema) => {
-                Ok(Response::new(ToolSchema {
-                    tool_id: schema.tool_id,
-                    json_schema: schema.json_schema,
-                }))
+
+        tracing::debug!("Getting schema for tool: {}", req.tool_id);
+
+        let tool = self.search_manager.get_tool(&req.tool_id).map_err(to_status)?;
+        let json_schema = tool.input_schema
+            .unwrap_or_else(|| serde_json::json!({ "type": "object" }))
+            .to_string();
+
+        Ok(Response::new(ToolSchema {
+            tool_id: tool.name,
+            json_schema,
+        }))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Request<ListResourcesRequest>,
+    ) -> Result<Response<ListResourcesResponse>, Status> {
+        let mut resources: Vec<Resource> = self.search_manager.list_all_resources()
+            .into_iter()
+            .map(|r| Resource {
+                uri: r.uri,
+                name: r.name,
+                description: r.description,
+                mime_type: r.mime_type,
+            })
+            .collect();
+        resources.sort_by(|a, b| a.uri.cmp(&b.uri));
+
+        Ok(Response::new(ListResourcesResponse { resources }))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Request<ListResourceTemplatesRequest>,
+    ) -> Result<Response<ListResourceTemplatesResponse>, Status> {
+        let mut resource_templates: Vec<ResourceTemplate> = self.search_manager.list_all_resource_templates()
+            .into_iter()
+            .map(|t| ResourceTemplate {
+                uri_template: t.uri_template,
+                name: t.name,
+                description: t.description,
+                mime_type: t.mime_type,
+            })
+            .collect();
+        resource_templates.sort_by(|a, b| a.uri_template.cmp(&b.uri_template));
+
+        Ok(Response::new(ListResourceTemplatesResponse { resource_templates }))
+    }
+
+    async fn read_resource(
+        &self,
+        request: Request<ReadResourceRequest>,
+    ) -> Result<Response<ReadResourceResponse>, Status> {
+        let req = request.into_inner();
+
+        tracing::debug!("Reading resource: {}", req.uri);
+
+        let content = self.search_manager.read_resource(&req.uri).await.map_err(|e| {
+            if !e.is_protocol_error() {
+                warn!("Failed to read resource {}: {}", req.uri, e);
             }
-            Err(e) => {
-                tracing::error!("Failed to get tool schema: {}", e);
-                Err(Status::internal(format!("Schema retrieval failed: {}", e)))
+            to_status(e)
+        })?;
+
+        Ok(Response::new(ReadResourceResponse {
+            uri: req.uri,
+            content: content.content,
+            mime_type: content.mime_type,
+        }))
+    }
+}
+
+/// Convert a tool to its gRPC form. The category is the owning service
+/// (`graph`, `memory`, ...) and the legacy `parameters_schema` maps each
+/// argument name to its JSON schema.
+fn to_proto_tool(tool: McpTool) -> Tool {
+    let category = tool.name.split('.').next().unwrap_or_default().to_string();
+    let name = tool.annotations
+        .as_ref()
+        .and_then(|a| a.title.clone())
+        .unwrap_or_else(|| tool.name.clone());
+    let parameters_schema = tool.input_schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(|properties| properties.as_object())
+        .map(|properties| properties.iter().map(|(k, v)| (k.clone(), v.to_string())).collect())
+        .unwrap_or_default();
+
+    let input_schema_json = tool.input_schema
+        .as_ref()
+        .map(|schema| schema.to_string())
+        .unwrap_or_else(|| r#"{"type":"object"}"#.to_string());
+
+    Tool {
+        tool_id: tool.name,
+        name,
+        description: tool.description,
+        category,
+        parameters_schema,
+        input_schema_json,
+        output_schema_json: tool.output_schema.map(|schema| schema.to_string()),
+    }
+}
+
+/// Build tool arguments from the legacy string map, using the tool's input
+/// schema to recover each argument's type. Values that don't fit their declared
+/// type are passed on unchanged so validation reports them.
+fn coerce_parameters(schema: Option<&Value>, parameters: HashMap<String, String>) -> Value {
+    let properties = schema.and_then(|schema| schema.get("properties"));
+    Value::Object(
+        parameters
+            .into_iter()
+            .map(|(key, raw)| {
+                let value = match properties.and_then(|p| p.get(&key)) {
+                    Some(property) => coerce_value(property, raw),
+                    None => parse_json_or_string(raw),
+                };
+                (key, value)
+            })
+            .collect(),
+    )
+}
+
+fn coerce_value(schema: &Value, raw: String) -> Value {
+    match schema_type(schema) {
+        Some("string") => Value::String(raw),
+        // Arrays may also be sent comma-separated: "code,docs"
+        Some("array") if !raw.trim_start().starts_with('[') => {
+            let items = schema.get("items").unwrap_or(&Value::Null);
+            Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| coerce_value(items, item.to_string()))
+                    .collect(),
+            )
+        }
+        _ => parse_json_or_string(raw),
+    }
+}
+
+/// The declared type, ignoring `null` in a union like `["string", "null"]`
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types.iter().filter_map(|t| t.as_str()).find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+fn parse_json_or_string(raw: String) -> Value {
+    serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+}
+
+/// Map request errors onto gRPC status codes, with the same message an MCP client gets
+fn to_status(error: McpError) -> Status {
+    let message = error.to_jsonrpc_error().message;
+    match error {
+        McpError::ToolNotFound(_) => Status::not_found(message),
+        McpError::InvalidArguments(_)
+        | McpError::ArgumentViolations { .. }
+        | McpError::InvalidRequest(_)
+        | McpError::ParseError(_) => Status::invalid_argument(message),
+        McpError::Unauthorized(_) => Status::unauthenticated(message),
+        McpError::RateLimited(_) => Status::resource_exhausted(message),
+        McpError::Http(_) => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
+
+/// Keep each backend's health status current. The overall (`""`) and
+/// `confuse.mcp.v1.Mcp` statuses stay SERVING: the service answers even while a
+/// backend is down, so probes shouldn't restart it over a backend outage.
+async fn report_backend_health(mut reporter: HealthReporter, search_manager: Arc<SearchManager>) {
+    let mut statuses: HashMap<&'static str, ServingStatus> = HashMap::new();
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        for (backend, result) in search_manager.check_backends().await {
+            let status = match &result {
+                Ok(()) => ServingStatus::Serving,
+                Err(_) => ServingStatus::NotServing,
+            };
+            if statuses.insert(backend, status) != Some(status) {
+                match result {
+                    Ok(()) => info!("💚 [gRPC] Backend {} is serving", backend),
+                    Err(e) => warn!("[gRPC] Backend {} is not serving: {}", backend, e),
+                }
+                reporter.set_service_status(backend, status).await;
             }
         }
     }
 }
 
+/// All gRPC services this process exposes, ready to serve: Mcp, health
+/// checking and server reflection
+pub async fn router(search_manager: Arc<SearchManager>) -> Router {
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter.set_serving::<McpServer<McpGrpcService>>().await;
+    // Backends are NOT_SERVING until their first check completes
+    for backend in search_manager.backends() {
+        health_reporter.set_service_status(backend, ServingStatus::NotServing).await;
+    }
+    tokio::spawn(report_backend_health(health_reporter, search_manager.clone()));
+
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build()
+        .expect("compiled-in descriptor sets are valid");
+
+    Server::builder()
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .add_service(McpServer::new(McpGrpcService::new(search_manager)))
+}
+
 pub async fn start_grpc_server(
-    service: Arc<McpService>,
-    config: Arc<Config>,
-) -> Result<(), Box<dyn std::error::Error>> {
+    search_manager: Arc<SearchManager>,
+    config: Arc<McpConfig>,
+) -> anyhow::Result<()> {
     let addr = format!("{}:{}", config.grpc_host, config.grpc_port).parse()?;
-    
-    tracing::info!("Starting gRPC server on {}", addr);
-    
-    let grpc_service = McpGrpcService::new(service, config);
-    
-    Server::builder()
-        .add_service(
-            crate::proto::confuse::mcp::v1::mcp_server::McpServer::new(grpc_service)
-        )
-        .serve(addr)
-        .await?;
-    
+
+    info!("🚀 [MCP Service] Starting gRPC server on {}", addr);
+
+    router(search_manager).await.serve(addr).await?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parameters(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn splits_comma_separated_arrays_by_item_type() {
+        let strings = json!({ "type": "array", "items": { "type": "string" } });
+        assert_eq!(coerce_value(&strings, "code, docs,,".to_string()), json!(["code", "docs"]));
+
+        let integers = json!({ "type": "array", "items": { "type": "integer" } });
+        assert_eq!(coerce_value(&integers, "1,2, 3".to_string()), json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn parses_json_array_input() {
+        let strings = json!({ "type": "array", "items": { "type": "string" } });
+        assert_eq!(coerce_value(&strings, r#"["a,b", "c"]"#.to_string()), json!(["a,b", "c"]));
+        assert_eq!(coerce_value(&strings, " []".to_string()), json!([]));
+    }
+
+    #[test]
+    fn ignores_null_in_union_types() {
+        let optional_string = json!({ "type": ["string", "null"] });
+        assert_eq!(coerce_value(&optional_string, "42".to_string()), json!("42"));
+
+        let optional_integer = json!({ "type": ["null", "integer"] });
+        assert_eq!(coerce_value(&optional_integer, "42".to_string()), json!(42));
+
+        let optional_array = json!({ "type": ["array", "null"], "items": { "type": "string" } });
+        assert_eq!(coerce_value(&optional_array, "a,b".to_string()), json!(["a", "b"]));
+    }
+
+    #[test]
+    fn passes_non_matching_values_through_unchanged() {
+        let integer = json!({ "type": "integer" });
+        assert_eq!(coerce_value(&integer, "ten".to_string()), json!("ten"));
+
+        let boolean = json!({ "type": "boolean" });
+        assert_eq!(coerce_value(&boolean, "yes".to_string()), json!("yes"));
+    }
+
+    #[test]
+    fn coerces_parameters_with_the_input_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer" },
+                "source_types": { "type": "array", "items": { "type": "string" } }
+            }
+        });
+        let coerced = coerce_parameters(
+            Some(&schema),
+            parameters(&[("query", "10"), ("limit", "10"), ("source_types", "code,docs"), ("extra", "true")]),
+        );
+        assert_eq!(coerced, json!({
+            "query": "10",
+            "limit": 10,
+            "source_types": ["code", "docs"],
+            "extra": true
+        }));
+
+        // Without a schema every value is parsed as JSON where possible
+        let coerced = coerce_parameters(None, parameters(&[("limit", "10"), ("query", "auth")]));
+        assert_eq!(coerced, json!({ "limit": 10, "query": "auth" }));
+    }
+}
@@ -1,69 +0,0 @@
-//! MCP Server gRPC Service
-//!
-//! Implements the Mcp gRPC service defined in proto/mcp.proto
-
-use tonic::{transport::Server, Request, Response, Status};
-use tracing::{info, error};
-
-/// MCP service implementation
-pub struct McpService {
-    // Add dependencies here
-}
-
-impl McpService {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
-
-// Implement the service when proto is generated
-// #[tonic::async_trait]
-// impl mcp::mcp_server::Mcp for McpService {
-//     async fn list_tools(
-//         &self,
-//         request: Request<mcp::ListToolsRequest>,
-//     ) -> Result<Response<mcp::ListToolsResponse>, Status> {
-//         info!("gRPC ListTools called");
-//         Err(Status::unimplemented("Not yet implemented"))
-//     }
-//     
-//     async fn call_tool(
-//         &self,
-//         request: Request<mcp::CallToolRequest>,
-//     ) -> Result<Response<mcp::CallToolResponse>, Status> {
-//         let req = request.into_inner();
-//         info!("gRPC CallTool called: {}", req.tool_id);
-//         Err(Status::unimplemented("Not yet implemented"))
-//     }
-//     
-//     async fn get_tool_schema(
-//         &self,
-//         request: Request<mcp::ToolSchemaRequest>,
-//     ) -> Result<Response<mcp::ToolSchema>, Status> {
-//         info!("gRPC GetToolSchema called");
-//         Err(Status::unimplemented("Not yet implemented"))
-//     }
-// }
-
-/// Start the gRPC server
-pub async fn serve() -> anyhow::Result<()> {
-    let grpc_port = std::env::var("GRPC_PORT")
-        .unwrap_or_else(|_| "50056".to_string())
-        .parse::<u16>()?;
-    
-    let addr = format!("0.0.0.0:{}", grpc_port).parse()?;
-    
-    let service = McpService::new();
-    
-    info!("Starting mcp-server gRPC server on {}", addr);
-    
-    // Uncomment when proto is generated:
-    // Server::builder()
-    //     .add_service(mcp::mcp_server::McpServer::new(service))
-    //     .serve(addr)
-    //     .await?;
-    
-    info!("gRPC server started successfully");
-    
-    Ok(())
-}
@@ -7,6 +7,21 @@ pub mod search;
 pub mod security;
 pub mod errors;
 pub mod db;
+pub mod grpc_server;
+
+/// Generated gRPC types for `proto/mcp.proto`
+pub mod proto {
+    pub mod confuse {
+        pub mod mcp {
+            pub mod v1 {
+                tonic::include_proto!("confuse.mcp.v1");
+
+                /// Encoded descriptors for gRPC server reflection
+                pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("mcp_descriptor");
+            }
+        }
+    }
+}
 
 pub use config::McpConfig;
 pub use errors::{McpError, McpResult};
@@ -3,9 +3,12 @@
 // It provides intelligent search and retrieval tools that query the knowledge graph
 // and fetch content from Azure Blob Storage based on search results
 use anyhow::Result;
-use mcp_service::{McpConfig, search::SearchManager, mcp::McpServer, db};
+use mcp_service::{McpConfig, search::SearchManager, mcp::{self, McpLogFilter, McpLogLayer, McpServer}, db, grpc_server};
+use std::sync::Arc;
 use actix_web::{web, App, HttpResponse, HttpServer};
 use tracing::info;
+use tracing_appender::{non_blocking::WorkerGuard, rolling::{RollingFileAppender, Rotation}};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 async fn health() -> HttpResponse {
     HttpResponse::Ok().json(serde_json::json!({
@@ -14,19 +17,57 @@ async fn health() -> HttpResponse {
     }))
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize structured logging
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
+/// Initialize logging to stderr or a rotating file, never stdout (the stdio transport).
+///
+/// Events are also forwarded to MCP clients that ask for them via `logging/setLevel`.
+/// The returned guard flushes the file writer and must be held until exit.
+fn init_logging(config: &McpConfig) -> Result<Option<WorkerGuard>> {
+    let (writer, guard) = match &config.log_dir {
+        Some(dir) => {
+            let rotation = match config.log_rotation.as_str() {
+                "minutely" => Rotation::MINUTELY,
+                "hourly" => Rotation::HOURLY,
+                "daily" => Rotation::DAILY,
+                "never" => Rotation::NEVER,
+                other => anyhow::bail!("LOG_ROTATION must be minutely, hourly, daily or never, got '{}'", other),
+            };
+            let appender = RollingFileAppender::new(rotation, dir, "mcp-service.log");
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (BoxMakeWriter::new(writer), Some(guard))
+        }
+        None => (BoxMakeWriter::new(std::io::stderr), None),
+    };
+
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(config.log_dir.is_none());
+    let fmt_layer = if config.log_json {
+        fmt_layer.json().boxed()
+    } else {
+        fmt_layer.boxed()
+    };
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::registry()
+        .with(fmt_layer.with_filter(filter))
+        // Clients get our own events only, at the level they ask for
+        .with(McpLogLayer::new().with_filter(McpLogFilter::new()))
         .init();
 
-    info!("Starting ConHub MCP Service");
+    Ok(guard)
+}
 
+#[tokio::main]
+async fn main() -> Result<()> {
     // Load configuration
     dotenv::dotenv().ok();
     let config = McpConfig::from_env()?;
 
+    let _log_guard = init_logging(&config)?;
+
+    info!("Starting ConHub MCP Service");
+
     // Initialize database stub
     let db_config = db::DatabaseConfig::from_env();
     info!("Initializing database");
@@ -41,24 +82,33 @@ async fn main() -> Result<()> {
         "Initialized search and retrieval services"
     );
 
-    // Start gRPC server
+    // Start gRPC server (tool API for the client-connector)
     let grpc_search_manager = Arc::new(search_manager.clone());
     let grpc_config = Arc::new(config.clone());
+    let (grpc_host, grpc_port) = (config.grpc_host.clone(), config.grpc_port);
     let grpc_handle = tokio::spawn(async move {
-        if let Err(e) = crate::grpc_server::start_grpc_server(grpc_search_manager, grpc_config).await {
+        if let Err(e) = grpc_server::start_grpc_server(grpc_search_manager, grpc_config).await {
             tracing::error!("gRPC server failed: {}", e);
         }
     });
 
-    // Start minimal HTTP server for health checks only
+    // MCP protocol handler shared by the stdio and Streamable HTTP transports
+    let server = McpServer::new(search_manager, config);
+    
+    // Start HTTP server (health checks + Streamable HTTP and WebSocket transports)
     let port = std::env::var("MCP_PORT").unwrap_or_else(|_| "3004".to_string());
     let port_num: u16 = port.parse().unwrap_or(3004);
     
+    let http_server = server.clone();
+    mcp::http::spawn_session_sweeper(server.clone());
     let http_handle = tokio::spawn(async move {
-        tracing::info!("🚀 [MCP Service] Starting health check server on port {}", port_num);
+        tracing::info!("🚀 [MCP Service] Starting HTTP server on port {}", port_num);
         HttpServer::new(move || {
             App::new()
+                .app_data(web::Data::new(http_server.clone()))
                 .route("/health", web::get().to(health))
+                .configure(mcp::http::configure)
+                .configure(mcp::ws::configure)
         })
         .bind(("0.0.0.0", port_num))
         .expect("Failed to bind MCP HTTP server")
@@ -68,7 +118,6 @@ async fn main() -> Result<()> {
     });
 
     // Start MCP server on stdio (main protocol)
-    let server = McpServer::new(search_manager, config);
     let mcp_handle = tokio::spawn(async move {
         match server.run().await {
             Ok(_) => {
@@ -82,9 +131,11 @@ async fn main() -> Result<()> {
 
     tracing::info!("✅ MCP service running");
     tracing::info!("   MCP Protocol: stdio");
+    tracing::info!("   MCP Streamable HTTP: http://0.0.0.0:{}/mcp", port_num);
+    tracing::info!("   MCP WebSocket: ws://0.0.0.0:{}/mcp/ws", port_num);
     tracing::info!("   Health Check: http://0.0.0.0:{}", port_num);
-    tracing::info!("   gRPC Server: 0.0.0.0:50056");
-    tracing::info!("   Tools: context_search, graph_query, embeddings_search, blob_retrieval");
+    tracing::info!("   gRPC Server: {}:{} (with grpc.health.v1 and reflection)", grpc_host, grpc_port);
+    tracing::info!("   Tools: context.*, graph.*, embeddings.*, memory.* (or context_* etc. for strict clients)");
     
     // Keep service running as long as HTTP health server is alive
     // Continue even if MCP stdio server exits (e.g., no attached client)
@@ -0,0 +1,87 @@
+// MCP Completions - Argument auto-completion for prompts, resource templates and tools
+//
+// Suggestions come from the search services, keyed by the tool argument they
+// apply to (`entity_id`, `repos`, `sources`, ...). Prompt arguments and resource
+// template variables are mapped onto those keys so every place an entity ID is
+// typed gets the same suggestions.
+use crate::{
+    errors::{McpError, McpResult},
+    mcp::{prompts, types::*},
+    search::{naming, SearchManager},
+};
+
+/// The spec caps a completion response at 100 values
+const MAX_VALUES: usize = 100;
+
+pub async fn complete(search_manager: &SearchManager, request: &CompleteRequest) -> McpResult<CompleteResult> {
+    let argument = &request.argument;
+
+    let Some(key) = argument_key(search_manager, &request.reference, &argument.name)? else {
+        return Ok(CompleteResult {
+            completion: Completion { values: Vec::new(), total: Some(0), has_more: Some(false) },
+        });
+    };
+
+    let mut values = search_manager.complete_argument(&key, &argument.value).await;
+    let total = values.len();
+    values.truncate(MAX_VALUES);
+
+    Ok(CompleteResult {
+        completion: Completion {
+            has_more: Some(total > values.len()),
+            total: Some(total),
+            values,
+        },
+    })
+}
+
+/// Map a referenced argument onto the tool argument name services complete.
+///
+/// Returns `None` for arguments that exist but have no completions.
+fn argument_key(search_manager: &SearchManager, reference: &CompletionReference, argument: &str) -> McpResult<Option<String>> {
+    match reference {
+        CompletionReference::Tool { name } => {
+            let canonical = naming::split_tool_name(name)
+                .map(|(service_id, tool_name)| format!("{}.{}", service_id, tool_name));
+            let tool = search_manager.list_all_tools()
+                .into_iter()
+                .find(|t| Some(&t.name) == canonical.as_ref())
+                .ok_or_else(|| McpError::InvalidArguments(format!("Unknown tool: {}", name)))?;
+
+            let declared = tool.input_schema
+                .as_ref()
+                .and_then(|schema| schema.pointer(&format!("/properties/{}", argument)))
+                .is_some();
+            Ok(declared.then(|| argument.to_string()))
+        }
+        CompletionReference::Prompt { name } => {
+            let prompt = prompts::list_prompts()
+                .into_iter()
+                .find(|p| &p.name == name)
+                .ok_or_else(|| McpError::InvalidArguments(format!("Unknown prompt: {}", name)))?;
+
+            if !prompt.arguments.iter().any(|a| a.name == argument) {
+                return Ok(None);
+            }
+            Ok(Some(match argument {
+                // investigate_error takes a single repo; memory.search takes a list
+                "repo" => "repos".to_string(),
+                other => other.to_string(),
+            }))
+        }
+        CompletionReference::Resource { uri } => {
+            let template = search_manager.list_all_resource_templates()
+                .into_iter()
+                .find(|t| &t.uri_template == uri)
+                .ok_or_else(|| McpError::InvalidArguments(format!("Unknown resource template: {}", uri)))?;
+
+            if !template.uri_template.contains(&format!("{{{}}}", argument)) {
+                return Ok(None);
+            }
+            Ok(Some(match (template.uri_template.starts_with("graph://entity/"), argument) {
+                (true, "id") => "entity_id".to_string(),
+                (_, other) => other.to_string(),
+            }))
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Tool Call Context - Per-call handle back to the requesting client
+use crate::{
+    errors::{McpError, McpResult},
+    mcp::{session::McpSession, types::Root},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long to wait for `sampling/createMessage`; clients may ask the user to approve it
+const SAMPLING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Intermediate output of a tool call, delivered to streaming callers
+#[derive(Debug, Clone)]
+pub enum ToolEvent {
+    Progress {
+        progress: f64,
+        total: Option<f64>,
+        message: String,
+    },
+    /// Results available before the call completes, e.g. vector hits while graph search runs
+    Partial {
+        stage: String,
+        result: Value,
+    },
+}
+
+/// Context handed to `SearchService::call_tool` for a single tool call
+///
+/// Carries the client's progress token (from `_meta.progressToken`) and a handle
+/// to the session so long-running tools can report back while they work. Calls
+/// made outside an MCP session use [`ToolContext::detached`], which drops all
+/// notifications, or [`ToolContext::streaming`], which forwards progress and
+/// partial results as [`ToolEvent`]s.
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    session: Option<Arc<McpSession>>,
+    progress_token: Option<Value>,
+    events: Option<mpsc::UnboundedSender<ToolEvent>>,
+}
+
+impl ToolContext {
+    pub fn new(session: Arc<McpSession>, progress_token: Option<Value>) -> Self {
+        Self {
+            session: Some(session),
+            progress_token,
+            events: None,
+        }
+    }
+
+    /// Context for calls that have no client session to report to
+    pub fn detached() -> Self {
+        Self::default()
+    }
+
+    /// Context for calls whose caller consumes progress and partial results as events
+    pub fn streaming(events: mpsc::UnboundedSender<ToolEvent>) -> Self {
+        Self {
+            events: Some(events),
+            ..Self::default()
+        }
+    }
+
+    pub fn session(&self) -> Option<&Arc<McpSession>> {
+        self.session.as_ref()
+    }
+
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.progress_token.as_ref()
+    }
+
+    /// Repository names of the client's workspace roots (empty if unknown)
+    pub fn root_repos(&self) -> Vec<String> {
+        self.session
+            .as_ref()
+            .map(|session| session.roots().iter().filter_map(Root::repo_name).collect())
+            .unwrap_or_default()
+    }
+
+    /// Ask the client's model to answer `prompt` via `sampling/createMessage`.
+    ///
+    /// Returns `None` without contacting the client if there is no session or the
+    /// client did not declare the `sampling` capability.
+    pub async fn sample(&self, prompt: &str, max_tokens: u32) -> Option<McpResult<String>> {
+        let session = self.session.as_ref().filter(|s| s.client_supports("sampling"))?;
+
+        let params = json!({
+            "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": prompt }
+            }],
+            "includeContext": "none",
+            "maxTokens": max_tokens,
+            "modelPreferences": { "speedPriority": 0.8, "intelligencePriority": 0.3 }
+        });
+
+        let result = session.request("sampling/createMessage", params, SAMPLING_TIMEOUT).await;
+        Some(result.and_then(|result| {
+            result.pointer("/content/text")
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| McpError::Internal("Client returned no text from sampling/createMessage".into()))
+        }))
+    }
+
+    /// Report progress: emits `notifications/progress` if the client sent a
+    /// progress token, and a [`ToolEvent::Progress`] to streaming callers.
+    pub fn progress(&self, progress: f64, total: Option<f64>, message: impl Into<String>) {
+        let message = message.into();
+        if let Some(events) = &self.events {
+            let _ = events.send(ToolEvent::Progress { progress, total, message: message.clone() });
+        }
+
+        let Some(token) = &self.progress_token else {
+            return;
+        };
+
+        let mut params = json!({
+            "progressToken": token,
+            "progress": progress,
+            "message": message
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+
+        self.notify("notifications/progress", params);
+    }
+
+    /// Hand results to a streaming caller before the call completes. MCP
+    /// clients only see the final result, so this is a no-op for them.
+    pub fn partial(&self, stage: &str, result: Value) {
+        if let Some(events) = &self.events {
+            let _ = events.send(ToolEvent::Partial { stage: stage.to_string(), result });
+        }
+    }
+
+    /// Send an arbitrary notification to the client. Returns false if it could not be queued.
+    pub fn notify(&self, method: &str, params: Value) -> bool {
+        match &self.session {
+            Some(session) => session.send(json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params
+            })),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// MCP Streamable HTTP Transport
+//
+// POST /mcp    - JSON-RPC messages from the client (responses returned in the body)
+// GET /mcp     - Optional SSE stream for server-to-client messages
+// DELETE /mcp  - Explicit session termination
+//
+// Sessions are created by `initialize` and identified by the `Mcp-Session-Id` header.
+// Sessions without an open SSE stream or request expire after `MCP_SESSION_TTL_SECS`.
+// Requests from browser origins not listed in `MCP_ALLOWED_ORIGINS` are rejected with 403.
+use crate::mcp::{logging::WIRE_TARGET, server::McpServer, session::{McpSession, SESSION_HEADER}, version::ProtocolVersion};
+use actix_web::{http::{header, StatusCode}, web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures::Stream;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Header carrying the negotiated protocol revision on requests after `initialize`
+const PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+
+/// How often idle sessions are looked for
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Register the MCP routes on an actix app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/mcp")
+            .route(web::post().to(handle_post))
+            .route(web::get().to(handle_get))
+            .route(web::delete().to(handle_delete)),
+    );
+}
+
+/// Close idle sessions until the process exits
+pub fn spawn_session_sweeper(server: McpServer) -> tokio::task::JoinHandle<()> {
+    let ttl = Duration::from_secs(server.config().session_ttl_secs);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SESSION_SWEEP_INTERVAL.min(ttl).max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            for id in server.sessions().expire_idle(ttl) {
+                info!("🌐 Expired idle HTTP session {}", id);
+            }
+        }
+    })
+}
+
+async fn handle_post(req: HttpRequest, body: Bytes, server: web::Data<McpServer>) -> HttpResponse {
+    if let Some(resp) = reject_origin(&req, &server) {
+        return resp;
+    }
+
+    let body = match std::str::from_utf8(&body) {
+        Ok(body) => body,
+        Err(_) => return transport_error(StatusCode::BAD_REQUEST, -32700, "Request body is not valid UTF-8"),
+    };
+
+    let message: Value = match serde_json::from_str(body) {
+        Ok(message) => message,
+        Err(e) => return transport_error(StatusCode::BAD_REQUEST, -32700, &format!("Parse error: {}", e)),
+    };
+
+    let is_initialize = message.get("method").and_then(|m| m.as_str()) == Some("initialize");
+
+    let existing = match session_id(&req) {
+        Some(id) => match server.sessions().get(id) {
+            Some(session) => Some(session),
+            None => return transport_error(StatusCode::NOT_FOUND, -32001, "Session not found"),
+        },
+        None if is_initialize => None,
+        None => return transport_error(StatusCode::BAD_REQUEST, -32000, "Missing Mcp-Session-Id header"),
+    };
+
+    // Checked before `initialize` gets a session so a rejected request leaves nothing behind
+    if let Some(resp) = reject_protocol_version(&req, existing.as_deref()) {
+        return resp;
+    }
+
+    let session = match existing {
+        Some(session) => session,
+        None => {
+            let session = server.sessions().create();
+            info!("🌐 Opened HTTP session {}", session.id());
+            session
+        }
+    };
+    session.touch();
+
+    debug!(target: WIRE_TARGET, "📨 [{}] Received message: {}", session.id(), body);
+
+    // Notifications and batches of notifications produce no response body
+    let Some(response) = server.handle_message(&session, body).await else {
+        return HttpResponse::Accepted().finish();
+    };
+
+    // A failed initialize must not leave a dangling session behind
+    if is_initialize && response.get("error").is_some() {
+        server.sessions().remove(session.id());
+    }
+
+    HttpResponse::Ok()
+        .insert_header((SESSION_HEADER, session.id()))
+        .json(response)
+}
+
+async fn handle_get(req: HttpRequest, server: web::Data<McpServer>) -> HttpResponse {
+    if let Some(resp) = reject_origin(&req, &server) {
+        return resp;
+    }
+
+    let session = match resolve_session(&req, &server) {
+        Ok(session) => session,
+        Err(resp) => return resp,
+    };
+    if let Some(resp) = reject_protocol_version(&req, Some(&session)) {
+        return resp;
+    }
+    session.touch();
+
+    let Some(rx) = session.take_outbound() else {
+        return transport_error(StatusCode::CONFLICT, -32000, "An SSE stream is already open for this session");
+    };
+
+    info!("📡 [{}] SSE stream attached", session.id());
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header((SESSION_HEADER, session.id()))
+        .streaming(SseStream { session, rx: Some(rx) })
+}
+
+async fn handle_delete(req: HttpRequest, server: web::Data<McpServer>) -> HttpResponse {
+    if let Some(resp) = reject_origin(&req, &server) {
+        return resp;
+    }
+
+    let Some(id) = session_id(&req) else {
+        return transport_error(StatusCode::BAD_REQUEST, -32000, "Missing Mcp-Session-Id header");
+    };
+
+    match server.sessions().remove(id) {
+        Some(_) => {
+            info!("🌐 Closed HTTP session {}", id);
+            HttpResponse::NoContent().finish()
+        }
+        None => transport_error(StatusCode::NOT_FOUND, -32001, "Session not found"),
+    }
+}
+
+fn session_id(req: &HttpRequest) -> Option<&str> {
+    req.headers().get(SESSION_HEADER).and_then(|v| v.to_str().ok())
+}
+
+fn resolve_session(req: &HttpRequest, server: &McpServer) -> Result<Arc<McpSession>, HttpResponse> {
+    let id = session_id(req)
+        .ok_or_else(|| transport_error(StatusCode::BAD_REQUEST, -32000, "Missing Mcp-Session-Id header"))?;
+
+    server.sessions()
+        .get(id)
+        .ok_or_else(|| transport_error(StatusCode::NOT_FOUND, -32001, "Session not found"))
+}
+
+/// 400 response if the `MCP-Protocol-Version` header names a revision we don't
+/// speak, or differs from the one negotiated for the session
+fn reject_protocol_version(req: &HttpRequest, session: Option<&McpSession>) -> Option<HttpResponse> {
+    let header = req.headers().get(PROTOCOL_VERSION_HEADER)?;
+    let Some(requested) = header.to_str().ok().and_then(ProtocolVersion::parse) else {
+        return Some(transport_error(StatusCode::BAD_REQUEST, -32600, "Unsupported MCP-Protocol-Version header"));
+    };
+
+    match session.and_then(|session| session.negotiated_protocol_version()) {
+        Some(negotiated) if negotiated != requested => Some(transport_error(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            &format!("MCP-Protocol-Version {} does not match the negotiated version {}", requested, negotiated),
+        )),
+        _ => None,
+    }
+}
+
+/// 403 response for browser requests from origins that are not explicitly allowed
+/// (DNS rebinding protection), or `None` if the request may proceed
+pub(crate) fn reject_origin(req: &HttpRequest, server: &McpServer) -> Option<HttpResponse> {
+    let origin = req.headers().get(header::ORIGIN)?;
+
+    match origin.to_str() {
+        Ok(origin) if server.config().origin_allowed(origin) => None,
+        _ => {
+            warn!("🚫 Rejected MCP request from origin {:?}", origin);
+            Some(transport_error(StatusCode::FORBIDDEN, -32000, "Origin not allowed"))
+        }
+    }
+}
+
+/// Transport-level failure, reported as a JSON-RPC error without an id
+fn transport_error(status: StatusCode, code: i32, message: &str) -> HttpResponse {
+    HttpResponse::build(status).json(json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    }))
+}
+
+/// SSE body that drains a session's outbound channel.
+///
+/// The receiver is handed back to the session when the client disconnects so a
+/// later GET can resume the stream. The stream ends when the session is closed.
+struct SseStream {
+    session: Arc<McpSession>,
+    rx: Option<mpsc::UnboundedReceiver<Value>>,
+}
+
+impl Stream for SseStream {
+    type Item = Result<Bytes, actix_web::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Some(rx) = self.rx.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        rx.poll_recv(cx).map(|message| {
+            message.map(|message| Ok(Bytes::from(format!("event: message\ndata: {}\n\n", message))))
+        })
+    }
+}
+
+impl Drop for SseStream {
+    fn drop(&mut self) {
+        if let Some(rx) = self.rx.take() {
+            self.session.restore_outbound(rx);
+        }
+    }
+}
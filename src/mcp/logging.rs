@@ -0,0 +1,312 @@
+// MCP Logging - Bridges `tracing` events to clients as `notifications/message`
+//
+// Requests are handled inside an `mcp_session` span carrying the session id.
+// Once a client picks a level with `logging/setLevel`, events from this crate
+// emitted within its requests (tool calls, backend fallbacks, ...) at or above
+// that level are forwarded to it. Events outside any session span stay local.
+//
+// `McpLogFilter` keeps the layer's call sites disabled beyond the most verbose
+// level any client asked for, so `debug!` costs nothing until a client wants it.
+// Raw JSON-RPC messages are logged under `WIRE_TARGET`, which is never
+// forwarded (a client would otherwise get its own traffic echoed back).
+use crate::mcp::session::McpSession;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Weak};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Name of the span requests are handled in
+pub const SESSION_SPAN: &str = "mcp_session";
+
+/// Target for logging raw JSON-RPC traffic; kept out of `notifications/message`
+pub const WIRE_TARGET: &str = concat!(env!("CARGO_CRATE_NAME"), "::wire");
+
+/// Sessions that asked for log messages, by session id
+static LOG_SINKS: Lazy<DashMap<String, Weak<McpSession>>> = Lazy::new(DashMap::new);
+
+/// Most verbose level any session asked for, as encoded by `encode_level`
+static MAX_CLIENT_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// RFC 5424 severities used by MCP logging, least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Critical => "critical",
+            LogLevel::Alert => "alert",
+            LogLevel::Emergency => "emergency",
+        }
+    }
+
+    pub fn parse(level: &str) -> Option<Self> {
+        match level {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "notice" => Some(LogLevel::Notice),
+            "warning" => Some(LogLevel::Warning),
+            "error" => Some(LogLevel::Error),
+            "critical" => Some(LogLevel::Critical),
+            "alert" => Some(LogLevel::Alert),
+            "emergency" => Some(LogLevel::Emergency),
+            _ => None,
+        }
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        match self {
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Info | LogLevel::Notice => LevelFilter::INFO,
+            LogLevel::Warning => LevelFilter::WARN,
+            _ => LevelFilter::ERROR,
+        }
+    }
+
+    fn from_tracing(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warning,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG | Level::TRACE => LogLevel::Debug,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Set a session's minimum log level and start forwarding log messages to it
+pub fn set_level(session: &Arc<McpSession>, level: LogLevel) {
+    session.set_log_level(level);
+    LOG_SINKS.insert(session.id().to_string(), Arc::downgrade(session));
+    update_max_level();
+}
+
+/// Stop forwarding log messages to a session (called when it is dropped)
+pub fn remove_sink(session_id: &str) {
+    if LOG_SINKS.remove(session_id).is_some() {
+        update_max_level();
+    }
+}
+
+/// Recompute the most verbose requested level and re-evaluate call sites if it changed
+fn update_max_level() {
+    // Upgrade outside the map: dropping the last reference re-enters `remove_sink`
+    let sinks: Vec<Weak<McpSession>> = LOG_SINKS.iter().map(|sink| sink.value().clone()).collect();
+    let max = sinks.iter()
+        .filter_map(Weak::upgrade)
+        .filter_map(|session| session.log_level())
+        .map(|level| level.level_filter())
+        .max()
+        .unwrap_or(LevelFilter::OFF);
+
+    let encoded = encode_level(max);
+    if MAX_CLIENT_LEVEL.swap(encoded, Ordering::Relaxed) != encoded {
+        tracing::callsite::rebuild_interest_cache();
+    }
+}
+
+fn max_client_level() -> LevelFilter {
+    match MAX_CLIENT_LEVEL.load(Ordering::Relaxed) {
+        0 => LevelFilter::OFF,
+        1 => LevelFilter::ERROR,
+        2 => LevelFilter::WARN,
+        3 => LevelFilter::INFO,
+        4 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+fn encode_level(level: LevelFilter) -> u8 {
+    match level.into_level() {
+        None => 0,
+        Some(Level::ERROR) => 1,
+        Some(Level::WARN) => 2,
+        Some(Level::INFO) => 3,
+        Some(Level::DEBUG) => 4,
+        Some(Level::TRACE) => 5,
+    }
+}
+
+/// Per-layer filter for [`McpLogLayer`]: this crate's events (minus wire logging)
+/// up to the most verbose level a client set with `logging/setLevel`
+#[derive(Debug, Default)]
+pub struct McpLogFilter;
+
+impl McpLogFilter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn is_enabled(metadata: &Metadata<'_>) -> bool {
+        let max = max_client_level();
+        if max == LevelFilter::OFF {
+            return false;
+        }
+        // The session span must be seen at any level so events can find their session
+        if metadata.is_span() {
+            return metadata.name() == SESSION_SPAN;
+        }
+        metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && !metadata.target().starts_with(WIRE_TARGET)
+            && *metadata.level() <= max
+    }
+}
+
+impl<S> Filter<S> for McpLogFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: &Context<'_, S>) -> bool {
+        Self::is_enabled(metadata)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        // Re-evaluated via `rebuild_interest_cache` whenever the level changes
+        if Self::is_enabled(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        match max_client_level() {
+            LevelFilter::OFF => Some(LevelFilter::OFF),
+            // Leave room for the INFO session span
+            max => Some(max.max(LevelFilter::INFO)),
+        }
+    }
+}
+
+/// `tracing` layer that forwards events to the session whose request emitted them
+#[derive(Debug, Default)]
+pub struct McpLogLayer;
+
+impl McpLogLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Session id recorded on `mcp_session` spans
+struct SessionId(String);
+
+impl<S> Layer<S> for McpLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != SESSION_SPAN {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(Value::String(session_id)), Some(span)) = (visitor.fields.remove("session_id"), ctx.span(id)) {
+            span.extensions_mut().insert(SessionId(session_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if LOG_SINKS.is_empty() {
+            return;
+        }
+
+        let Some(session_id) = ctx.event_scope(event).and_then(|mut scope| {
+            scope.find_map(|span| span.extensions().get::<SessionId>().map(|s| s.0.clone()))
+        }) else {
+            return;
+        };
+
+        let session = LOG_SINKS.get(&session_id).and_then(|sink| sink.upgrade());
+        let Some(session) = session else {
+            return;
+        };
+
+        let level = LogLevel::from_tracing(metadata.level());
+        if session.log_level().is_none_or(|min| level < min) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let data = match visitor.fields.remove("message") {
+            Some(message) if visitor.fields.is_empty() => message,
+            Some(message) => {
+                visitor.fields.insert("message".to_string(), message);
+                Value::Object(visitor.fields)
+            }
+            None => Value::Object(visitor.fields),
+        };
+
+        session.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": level.as_str(),
+                "logger": metadata.target(),
+                "data": data
+            }
+        }));
+    }
+}
+
+/// Collects event/span fields as JSON
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields.insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
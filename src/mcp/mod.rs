@@ -1,6 +1,20 @@
 // MCP Protocol - JSON-RPC implementation
+pub mod completion;
+pub mod context;
+pub mod http;
+pub mod logging;
+pub mod prompts;
+pub mod roots;
 pub mod server;
+pub mod session;
+pub mod subscriptions;
 pub mod types;
+pub mod version;
+pub mod ws;
 
+pub use context::{ToolContext, ToolEvent};
+pub use logging::{McpLogFilter, McpLogLayer};
 pub use server::McpServer;
+pub use session::{McpSession, SessionStore};
 pub use types::*;
+pub use version::ProtocolVersion;
@@ -0,0 +1,190 @@
+// MCP Prompts - Built-in prompt templates filled with live knowledge-layer data
+//
+// Each prompt gathers context through the same tools agents can call directly
+// (graph, hybrid and memory services) and renders it into a ready-made workflow.
+use crate::{
+    errors::{McpError, McpResult},
+    mcp::{context::ToolContext, types::*},
+    search::SearchManager,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// List the built-in prompt templates
+pub fn list_prompts() -> Vec<McpPrompt> {
+    vec![
+        McpPrompt {
+            name: "explain_entity".to_string(),
+            description: "Explain an entity from the knowledge graph, including its relationships".to_string(),
+            arguments: vec![
+                PromptArgument {
+                    name: "entity_id".to_string(),
+                    description: "Entity ID to explain".to_string(),
+                    required: true,
+                },
+                PromptArgument {
+                    name: "depth".to_string(),
+                    description: "Relationship depth to include (default: 1)".to_string(),
+                    required: false,
+                },
+            ],
+        },
+        McpPrompt {
+            name: "investigate_error".to_string(),
+            description: "Investigate an error message using related code, docs, tickets and chat".to_string(),
+            arguments: vec![
+                PromptArgument {
+                    name: "error".to_string(),
+                    description: "Error message or stack trace".to_string(),
+                    required: true,
+                },
+                PromptArgument {
+                    name: "repo".to_string(),
+                    description: "Optional: Repository to focus the search on".to_string(),
+                    required: false,
+                },
+            ],
+        },
+        McpPrompt {
+            name: "summarize_robot_episodes".to_string(),
+            description: "Summarize a robot's recent episodes and current situation".to_string(),
+            arguments: vec![
+                PromptArgument {
+                    name: "robot_id".to_string(),
+                    description: "UUID of the robot".to_string(),
+                    required: true,
+                },
+                PromptArgument {
+                    name: "focus".to_string(),
+                    description: "Optional: What to focus on (default: recent episodes)".to_string(),
+                    required: false,
+                },
+            ],
+        },
+    ]
+}
+
+/// Render a prompt template with live data
+pub async fn get_prompt(
+    search_manager: &SearchManager,
+    request: &PromptGetRequest,
+    ctx: &ToolContext,
+) -> McpResult<PromptGetResult> {
+    let prompt = list_prompts()
+        .into_iter()
+        .find(|p| p.name == request.name)
+        .ok_or_else(|| McpError::InvalidArguments(format!("Unknown prompt: {}", request.name)))?;
+
+    for argument in prompt.arguments.iter().filter(|a| a.required) {
+        if !request.arguments.contains_key(&argument.name) {
+            return Err(McpError::InvalidArguments(format!(
+                "Missing required argument '{}' for prompt {}", argument.name, prompt.name
+            )));
+        }
+    }
+
+    let args = &request.arguments;
+    let text = match prompt.name.as_str() {
+        "explain_entity" => explain_entity(search_manager, args, ctx).await?,
+        "investigate_error" => investigate_error(search_manager, args, ctx).await,
+        "summarize_robot_episodes" => summarize_robot_episodes(search_manager, args, ctx).await,
+        other => return Err(McpError::Internal(format!("Prompt {} is listed but has no renderer", other))),
+    };
+
+    Ok(PromptGetResult {
+        description: prompt.description,
+        messages: vec![PromptMessage::user(text)],
+    })
+}
+
+async fn explain_entity(
+    search_manager: &SearchManager,
+    args: &HashMap<String, String>,
+    ctx: &ToolContext,
+) -> McpResult<String> {
+    let entity_id = &args["entity_id"];
+    let depth: u64 = match args.get("depth") {
+        Some(depth) => depth.parse()
+            .map_err(|_| McpError::InvalidArguments(format!("depth must be an integer, got '{}'", depth)))?,
+        None => 1,
+    };
+
+    let (entity, related) = tokio::join!(
+        fetch(search_manager, "graph.get_entity", json!({ "entity_id": entity_id }), ctx),
+        fetch(search_manager, "context.related", json!({ "entity_id": entity_id, "depth": depth }), ctx),
+    );
+
+    Ok(format!(
+        "Explain the entity `{entity_id}` from our knowledge graph.\n\n\
+         Cover what it is, what it is responsible for, how it relates to its neighbors, \
+         and anything a developer should know before changing it.\n\n\
+         ## Entity\n```json\n{entity}\n```\n\n\
+         ## Related entities (depth {depth})\n```json\n{related}\n```"
+    ))
+}
+
+async fn investigate_error(
+    search_manager: &SearchManager,
+    args: &HashMap<String, String>,
+    ctx: &ToolContext,
+) -> String {
+    let error = &args["error"];
+
+    let mut memory_args = json!({
+        "query": error,
+        "sources": ["tickets", "chat"],
+        "max_blocks": 10
+    });
+    if let Some(repo) = args.get("repo") {
+        memory_args["repos"] = json!([repo]);
+    }
+
+    let (code, discussions) = tokio::join!(
+        fetch(search_manager, "context.search", json!({ "query": error, "limit": 10 }), ctx),
+        fetch(search_manager, "memory.search", memory_args, ctx),
+    );
+
+    format!(
+        "Investigate the following error and propose likely root causes and fixes.\n\n\
+         ## Error\n```\n{error}\n```\n\n\
+         ## Relevant code and documentation\n```json\n{code}\n```\n\n\
+         ## Related tickets and discussions\n```json\n{discussions}\n```\n\n\
+         Rank candidate causes by likelihood and cite the entities or files you rely on."
+    )
+}
+
+async fn summarize_robot_episodes(
+    search_manager: &SearchManager,
+    args: &HashMap<String, String>,
+    ctx: &ToolContext,
+) -> String {
+    let robot_id = &args["robot_id"];
+    let focus = args.get("focus").map(String::as_str).unwrap_or("recent episodes");
+
+    let (context, episodes) = tokio::join!(
+        fetch(search_manager, "memory.robot_context", json!({ "robot_id": robot_id }), ctx),
+        fetch(search_manager, "memory.robot_search", json!({
+            "robot_id": robot_id,
+            "query": focus,
+            "include_semantic": false
+        }), ctx),
+    );
+
+    format!(
+        "Summarize the {focus} for robot `{robot_id}`.\n\n\
+         Describe what the robot did, anything unusual or failed, and what it is doing now.\n\n\
+         ## Current context\n```json\n{context}\n```\n\n\
+         ## Episodes\n```json\n{episodes}\n```"
+    )
+}
+
+/// Call a tool and render its result for a prompt.
+///
+/// Backend failures are embedded in the prompt text instead of failing the whole
+/// request, so the agent still gets whatever context is available.
+async fn fetch(search_manager: &SearchManager, tool: &str, args: Value, ctx: &ToolContext) -> String {
+    match search_manager.call_tool(tool, args, ctx).await {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+        Err(e) => format!("\"(unavailable: {})\"", e),
+    }
+}
@@ -0,0 +1,44 @@
+// MCP Roots - The client's workspace folders, used to scope searches
+//
+// Clients that declare the `roots` capability are asked for `roots/list` once
+// they finish initializing and again on `notifications/roots/list_changed`.
+// The roots' repository names become the default `repos` filter for search tools.
+use crate::{
+    errors::{McpError, McpResult},
+    mcp::{session::McpSession, types::Root},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn, Instrument};
+
+/// How long to wait for the client to answer `roots/list`
+const ROOTS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Re-fetch the session's roots in the background.
+///
+/// Runs detached because the client answers through the same transport that is
+/// delivering the notification that triggered the refresh.
+pub fn refresh(session: &Arc<McpSession>) {
+    let session = session.clone();
+    tokio::spawn(
+        async move {
+            match fetch(&session).await {
+                Ok(roots) => {
+                    let repos: Vec<String> = roots.iter().filter_map(Root::repo_name).collect();
+                    info!("📁 [{}] Workspace roots: {}", session.id(), repos.join(", "));
+                    session.set_roots(roots);
+                }
+                Err(e) => warn!("[{}] Failed to list roots: {}", session.id(), e),
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+async fn fetch(session: &McpSession) -> McpResult<Vec<Root>> {
+    let result = session.request("roots/list", json!({}), ROOTS_TIMEOUT).await?;
+    let roots = result.get("roots").cloned().unwrap_or(Value::Array(Vec::new()));
+    serde_json::from_value(roots)
+        .map_err(|e| McpError::Internal(format!("Invalid roots/list result: {}", e)))
+}
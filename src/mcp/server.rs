@@ -1,86 +1,237 @@
 // MCP Server - JSON-RPC handler
 use crate::{
     config::McpConfig,
-    search::SearchManager,
+    search::{SearchManager, ToolNaming},
     mcp::types::*,
-    errors::{McpError, McpResult},
+    mcp::session::{McpSession, SessionStore},
+    mcp::context::ToolContext,
+    mcp::{completion, logging::{self, WIRE_TARGET}, prompts, roots, subscriptions},
+    mcp::version::ProtocolVersion,
+    errors::{McpError, McpErrorResponse, McpResult},
 };
 use anyhow::Result;
+use futures::future;
 use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing::{info, error, debug};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{info, info_span, warn, error, debug, Instrument};
 
+/// MCP protocol handler shared by all transports (stdio, Streamable HTTP)
+#[derive(Clone)]
 pub struct McpServer {
-    search_manager: SearchManager,
-    config: McpConfig,
+    search_manager: Arc<SearchManager>,
+    config: Arc<McpConfig>,
+    sessions: Arc<SessionStore>,
 }
 
 impl McpServer {
     pub fn new(search_manager: SearchManager, config: McpConfig) -> Self {
         Self {
-            search_manager,
-            config,
+            search_manager: Arc::new(search_manager),
+            config: Arc::new(config),
+            sessions: Arc::new(SessionStore::new()),
         }
     }
     
-    pub async fn run(mut self) -> Result<()> {
+    pub fn config(&self) -> &McpConfig {
+        &self.config
+    }
+    
+    /// Sessions opened over the Streamable HTTP transport
+    pub fn sessions(&self) -> &SessionStore {
+        &self.sessions
+    }
+    
+    /// Serve a single client over stdin/stdout.
+    ///
+    /// Each request is dispatched onto its own task so a slow tool call never blocks
+    /// the rest of the session. Responses are written as they complete (clients
+    /// correlate them by `id`) by a single writer task that owns stdout.
+    pub async fn run(self) -> Result<()> {
         info!("🔗 ConHub MCP Server starting on stdio");
         info!("📡 Model Context Protocol ready");
         info!("🔌 {} search services enabled", self.search_manager.service_count());
         
-        let stdin = tokio::io::stdin();
-        let mut stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
-        let mut line = String::new();
+        let session = McpSession::new();
+        let outbound = session
+            .take_outbound()
+            .expect("fresh session always has an outbound channel");
+        let writer = tokio::spawn(write_stdout(outbound));
+        
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut in_flight = JoinSet::new();
         
         loop {
-            line.clear();
-            let n = reader.read_line(&mut line).await?;
-            if n == 0 {
-                break; // EOF
-            }
-            
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            
-            debug!("📨 Received request: {}", line);
-            
-            let response = match self.handle_request(line).await {
-                Ok(resp) => resp,
-                Err(e) => {
-                    error!("❌ Error handling request: {}", e);
-                    self.error_response(None, McpError::Other(e))
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        break; // EOF
+                    };
+                    
+                    let line = line.trim().to_string();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    
+                    let server = self.clone();
+                    let session = session.clone();
+                    in_flight.spawn(async move {
+                        server.respond(&session, &line).await;
+                    });
                 }
-            };
-            
-            let response_str = serde_json::to_string(&response)?;
-            debug!("📤 Sending response: {}", response_str);
-            
-            stdout.write_all(response_str.as_bytes()).await?;
-            stdout.write_all(b"\n").await?;
-            stdout.flush().await?;
+                // Reap finished requests so the set doesn't grow unbounded
+                Some(_) = in_flight.join_next(), if !in_flight.is_empty() => {}
+            }
         }
         
+        // Let in-flight requests finish, then close the outbound channel so the writer exits
+        while in_flight.join_next().await.is_some() {}
+        drop(session);
+        writer.await??;
+        
         info!("MCP Server shutting down");
         Ok(())
     }
     
-    async fn handle_request(&mut self, request_str: &str) -> Result<JsonRpcResponse> {
-        let request: JsonRpcRequest = serde_json::from_str(request_str)?;
+    /// Handle one inbound message and queue its response (if any) on the session's outbound channel
+    pub async fn respond(&self, session: &Arc<McpSession>, message: &str) {
+        debug!(target: WIRE_TARGET, "📨 [{}] Received message: {}", session.id(), message);
+        
+        if let Some(response) = self.handle_message(session, message).await {
+            session.send(response);
+        }
+    }
+    
+    /// Handle one raw JSON-RPC message: a request, a notification or a batch.
+    ///
+    /// Returns the JSON to send back, or `None` when JSON-RPC 2.0 says nothing
+    /// should be sent (notifications, or batches made only of notifications).
+    pub async fn handle_message(&self, session: &Arc<McpSession>, message: &str) -> Option<Value> {
+        // Events inside this span are forwarded to the session once it sets a log level
+        let span = info_span!(logging::SESSION_SPAN, session_id = %session.id());
+        self.dispatch_message(session, message).instrument(span).await
+    }
+    
+    async fn dispatch_message(&self, session: &Arc<McpSession>, message: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("❌ [{}] Failed to parse message: {}", session.id(), e);
+                let response = self.error_response(
+                    Some(Value::Null),
+                    McpError::ParseError(format!("Parse error: {}", e)),
+                );
+                return serde_json::to_value(response).ok();
+            }
+        };
+        
+        match message {
+            Value::Array(batch) => {
+                let version = session.protocol_version();
+                if !version.supports_batching() {
+                    let response = self.error_response(
+                        Some(Value::Null),
+                        McpError::InvalidRequest(format!(
+                            "JSON-RPC batching is not supported in protocol version {}", version
+                        )),
+                    );
+                    return serde_json::to_value(response).ok();
+                }
+                
+                if batch.is_empty() {
+                    let response = self.error_response(
+                        Some(Value::Null),
+                        McpError::InvalidRequest("Empty batch".to_string()),
+                    );
+                    return serde_json::to_value(response).ok();
+                }
+                
+                let responses: Vec<JsonRpcResponse> = future::join_all(
+                    batch.into_iter().map(|item| self.handle_single(session, item))
+                )
+                .await
+                .into_iter()
+                .flatten()
+                .collect();
+                
+                if responses.is_empty() {
+                    None
+                } else {
+                    serde_json::to_value(responses).ok()
+                }
+            }
+            message => {
+                let response = self.handle_single(session, message).await?;
+                serde_json::to_value(response).ok()
+            }
+        }
+    }
+    
+    /// Handle a single (non-batch) message
+    async fn handle_single(&self, session: &Arc<McpSession>, message: Value) -> Option<JsonRpcResponse> {
+        // Responses to requests we sent the client (roots/list, ...) are routed to the waiting caller
+        if message.get("method").is_none() && (message.get("result").is_some() || message.get("error").is_some()) {
+            self.handle_client_response(session, message);
+            return None;
+        }
+        
+        // Only requests carry an id; notifications are handled but never answered
+        let is_notification = message.get("id").is_none();
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        
+        let request = match serde_json::from_value::<JsonRpcRequest>(message) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                return Some(self.error_response(
+                    Some(id),
+                    McpError::InvalidRequest("jsonrpc must be \"2.0\"".to_string()),
+                ));
+            }
+            Err(e) => {
+                return Some(self.error_response(
+                    Some(id),
+                    McpError::InvalidRequest(format!("Invalid request: {}", e)),
+                ));
+            }
+        };
         
+        if is_notification {
+            self.handle_notification(session, request).await;
+            return None;
+        }
+        
+        self.handle_request(session, request).await
+    }
+    
+    /// Handle a JSON-RPC request within a session.
+    ///
+    /// Returns `None` if the request was cancelled by the client, in which case no
+    /// response must be sent.
+    pub async fn handle_request(&self, session: &Arc<McpSession>, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
         let result = match request.method.as_str() {
             // Standard MCP protocol methods
-            "initialize" => self.initialize(request.params).await,
-            "tools/list" => self.list_tools().await,
-            "tools/call" => self.call_tool(request.params).await,
+            "initialize" => self.initialize(session, request.params).await,
+            "tools/list" => self.list_tools(session).await,
+            "tools/call" => {
+                let id = request.id.clone().unwrap_or(Value::Null);
+                self.call_tool_cancellable(session, &id, request.params).await?
+            }
             "resources/list" => self.list_resources().await,
             "resources/read" => self.read_resource(request.params).await,
+            "resources/templates/list" => self.list_resource_templates().await,
+            "resources/subscribe" => self.subscribe_resource(session, request.params).await,
+            "resources/unsubscribe" => self.unsubscribe_resource(session, request.params).await,
+            "prompts/list" => self.list_prompts().await,
+            "prompts/get" => self.get_prompt(session.clone(), request.params).await,
+            "logging/setLevel" => self.set_log_level(session, request.params).await,
+            "completion/complete" => self.complete(request.params).await,
             
             // Legacy compatibility (can be removed later)
-            "mcp.listTools" => self.list_tools().await,
-            "mcp.callTool" => self.call_tool(request.params).await,
+            "mcp.listTools" => self.list_tools(session).await,
+            "mcp.callTool" => self.call_tool(session.clone(), request.params).await,
             "mcp.listResources" => self.list_resources().await,
             "mcp.readResource" => self.read_resource(request.params).await,
             "mcp.health" => Ok(json!({"status": "healthy"})),
@@ -88,34 +239,111 @@ impl McpServer {
             _ => Err(McpError::ToolNotFound(format!("Unknown method: {}", request.method))),
         };
         
-        match result {
-            Ok(value) => Ok(JsonRpcResponse {
+        Some(match result {
+            Ok(value) => JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id: request.id,
                 result: Some(value),
                 error: None,
-            }),
-            Err(e) => Ok(self.error_response(request.id, e)),
+            },
+            Err(e) => self.error_response(request.id, e),
+        })
+    }
+    
+    /// Handle a JSON-RPC notification (no response is ever sent)
+    async fn handle_notification(&self, session: &Arc<McpSession>, notification: JsonRpcRequest) {
+        match notification.method.as_str() {
+            "notifications/initialized" => {
+                info!("✅ [{}] Client initialized", session.id());
+                if session.client_supports("roots") {
+                    roots::refresh(session);
+                }
+            }
+            "notifications/roots/list_changed" => {
+                debug!("[{}] Roots changed", session.id());
+                roots::refresh(session);
+            }
+            "notifications/cancelled" => {
+                let Some(request_id) = notification.params.as_ref().and_then(|p| p.get("requestId")) else {
+                    debug!("[{}] Cancellation without requestId", session.id());
+                    return;
+                };
+                
+                let reason = notification.params.as_ref()
+                    .and_then(|p| p.get("reason"))
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("no reason given");
+                
+                if session.cancel_request(request_id) {
+                    info!("🛑 [{}] Cancelled request {}: {}", session.id(), request_id, reason);
+                } else {
+                    debug!("[{}] Request {} is not running; cancelling it if it starts", session.id(), request_id);
+                }
+            }
+            method => {
+                debug!("[{}] Ignoring notification: {}", session.id(), method);
+            }
+        }
+    }
+    
+    fn handle_client_response(&self, session: &McpSession, response: Value) {
+        let id = response.get("id").cloned().unwrap_or(Value::Null);
+        let result = match response.get("error") {
+            Some(error) => Err(serde_json::from_value::<McpErrorResponse>(error.clone()).unwrap_or(McpErrorResponse {
+                code: -32603,
+                message: error.to_string(),
+                data: None,
+            })),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        
+        if !session.complete_request(&id, result) {
+            debug!("[{}] Ignoring response to unknown request {}", session.id(), id);
         }
     }
     
-    async fn initialize(&mut self, params: Option<Value>) -> McpResult<Value> {
+    async fn initialize(&self, session: &McpSession, params: Option<Value>) -> McpResult<Value> {
         info!("🔧 Initializing MCP connection");
         
+        let requested_version = params.as_ref()
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str());
+        let protocol_version = ProtocolVersion::negotiate(requested_version)?;
+        
+        // Explicit request via experimental capability, then server config, then client detection
+        let requested_naming = params.as_ref()
+            .and_then(|p| p.pointer("/capabilities/experimental/toolNaming"))
+            .and_then(|v| v.as_str())
+            .and_then(ToolNaming::parse);
+        
+        if let Some(capabilities) = params.as_ref().and_then(|p| p.get("capabilities")) {
+            session.set_client_capabilities(capabilities.clone());
+        }
+        
         // Parse client info if provided
         let client_info = params
             .and_then(|p| p.get("clientInfo").cloned())
             .and_then(|c| serde_json::from_value::<ClientInfo>(c).ok());
         
-        if let Some(info) = &client_info {
+        let tool_naming = requested_naming
+            .or(self.config.tool_naming)
+            .or_else(|| client_info.as_ref().map(|info| ToolNaming::detect(&info.name)))
+            .unwrap_or_default();
+        
+        if let Some(info) = client_info {
             info!("👤 Client: {} v{}", info.name, info.version);
+            session.set_client_info(info);
         }
         
+        info!("📜 [{}] Negotiated protocol version {}, {} tool names", session.id(), protocol_version, tool_naming.as_str());
+        session.set_protocol_version(protocol_version);
+        session.set_tool_naming(tool_naming);
+        
         Ok(json!({
-            "protocolVersion": "2024-11-05",
+            "protocolVersion": protocol_version.as_str(),
             "capabilities": {
                 "resources": {
-                    "subscribe": false,
+                    "subscribe": true,
                     "listChanged": false
                 },
                 "tools": {
@@ -124,7 +352,11 @@ impl McpServer {
                 "prompts": {
                     "listChanged": false
                 },
-                "logging": {}
+                "logging": {},
+                "completions": {},
+                "experimental": {
+                    "toolNaming": tool_naming.as_str()
+                }
             },
             "serverInfo": {
                 "name": "ConHub MCP Server",
@@ -133,29 +365,106 @@ impl McpServer {
         }))
     }
     
-    async fn list_tools(&self) -> McpResult<Value> {
-        let tools = self.search_manager.list_all_tools();
+    async fn list_tools(&self, session: &McpSession) -> McpResult<Value> {
+        let mut tools = self.search_manager.list_all_tools_as(session.tool_naming());
+        let version = session.protocol_version();
+        
+        for tool in &mut tools {
+            if !version.supports_structured_output() {
+                tool.output_schema = None;
+            }
+            if !version.supports_tool_annotations() {
+                tool.annotations = None;
+            }
+        }
+        
         Ok(json!({ "tools": tools }))
     }
     
-    async fn call_tool(&self, params: Option<Value>) -> McpResult<Value> {
+    async fn call_tool(&self, session: Arc<McpSession>, params: Option<Value>) -> McpResult<Value> {
         let call_request: ToolCallRequest = serde_json::from_value(
             params.ok_or_else(|| McpError::InvalidArguments("Missing params".to_string()))?
-        )?;
+        ).map_err(|e| McpError::InvalidArguments(format!("Invalid tools/call params: {}", e)))?;
+        
+        let progress_token = call_request.meta
+            .as_ref()
+            .and_then(|meta| meta.get("progressToken"))
+            .cloned();
+        let structured = session.protocol_version().supports_structured_output();
+        let ctx = ToolContext::new(session, progress_token);
         
-        let result = self.search_manager
-            .call_tool(&call_request.name, call_request.arguments)
-            .await?;
+        let result = match self.search_manager
+            .call_tool(&call_request.name, call_request.arguments, &ctx)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) if e.is_protocol_error() => return Err(e),
+            Err(e) => {
+                // Execution failures go back to the model so it can react to them
+                warn!("Tool {} failed: {}", call_request.name, e);
+                let tool_result = ToolCallResult::error(format!("Tool {} failed: {}", call_request.name, e));
+                return Ok(serde_json::to_value(tool_result)?);
+            }
+        };
         
-        let tool_result = ToolCallResult::success(serde_json::to_string(&result)?);
+        // SearchManager shapes results to the output schema, so they are objects;
+        // older clients only get the text copy
+        let tool_result = if structured && result.is_object() {
+            ToolCallResult::structured(result)
+        } else {
+            ToolCallResult::success(serde_json::to_string(&result)?)
+        };
         Ok(serde_json::to_value(tool_result)?)
     }
     
+    /// Run a tool call on its own task so `notifications/cancelled` can abort it.
+    ///
+    /// Aborting drops the tool's future, which in turn drops any in-flight reqwest
+    /// calls to the embeddings, graph and Ollama backends. Returns `None` when the
+    /// call was cancelled and no response must be sent.
+    async fn call_tool_cancellable(&self, session: &Arc<McpSession>, id: &Value, params: Option<Value>) -> Option<McpResult<Value>> {
+        let server = self.clone();
+        let task_session = session.clone();
+        let task = tokio::spawn(
+            async move { server.call_tool(task_session, params).await }.in_current_span()
+        );
+        let _in_flight = session.track_request(id, task.abort_handle());
+        
+        match task.await {
+            Ok(result) => Some(result),
+            Err(e) if e.is_cancelled() => None,
+            Err(e) => {
+                error!("Tool call task failed: {}", e);
+                let tool_result = ToolCallResult::error(format!("Tool call failed: {}", e));
+                Some(serde_json::to_value(tool_result).map_err(McpError::from))
+            }
+        }
+    }
+    
+    async fn list_prompts(&self) -> McpResult<Value> {
+        Ok(json!({ "prompts": prompts::list_prompts() }))
+    }
+    
+    async fn get_prompt(&self, session: Arc<McpSession>, params: Option<Value>) -> McpResult<Value> {
+        let request: PromptGetRequest = serde_json::from_value(
+            params.ok_or_else(|| McpError::InvalidArguments("Missing params".to_string()))?
+        ).map_err(|e| McpError::InvalidArguments(format!("Invalid prompts/get params: {}", e)))?;
+        
+        let ctx = ToolContext::new(session, None);
+        let prompt = prompts::get_prompt(&self.search_manager, &request, &ctx).await?;
+        Ok(serde_json::to_value(prompt)?)
+    }
+    
     async fn list_resources(&self) -> McpResult<Value> {
         let resources = self.search_manager.list_all_resources();
         Ok(json!({ "resources": resources }))
     }
     
+    async fn list_resource_templates(&self) -> McpResult<Value> {
+        let templates = self.search_manager.list_all_resource_templates();
+        Ok(json!({ "resourceTemplates": templates }))
+    }
+    
     async fn read_resource(&self, params: Option<Value>) -> McpResult<Value> {
         let params = params.ok_or_else(|| McpError::InvalidArguments("Missing params".to_string()))?;
         let resource_id: String = serde_json::from_value(
@@ -166,7 +475,47 @@ impl McpServer {
         Ok(serde_json::to_value(content)?)
     }
     
-    fn error_response(&self, id: Option<Value>, error: McpError) -> JsonRpcResponse {
+    async fn subscribe_resource(&self, session: &Arc<McpSession>, params: Option<Value>) -> McpResult<Value> {
+        let uri = resource_uri(params)?;
+        let poll_interval = Duration::from_secs(self.config.resource_poll_interval_secs.max(1));
+        
+        subscriptions::subscribe(&self.search_manager, session, &uri, poll_interval).await?;
+        Ok(json!({}))
+    }
+    
+    async fn unsubscribe_resource(&self, session: &Arc<McpSession>, params: Option<Value>) -> McpResult<Value> {
+        let uri = resource_uri(params)?;
+        
+        if session.unsubscribe(&uri) {
+            info!("🔕 [{}] Unsubscribed from {}", session.id(), uri);
+        }
+        Ok(json!({}))
+    }
+    
+    async fn complete(&self, params: Option<Value>) -> McpResult<Value> {
+        let request: CompleteRequest = serde_json::from_value(
+            params.ok_or_else(|| McpError::InvalidArguments("Missing params".to_string()))?
+        ).map_err(|e| McpError::InvalidArguments(format!("Invalid completion/complete params: {}", e)))?;
+        
+        let result = completion::complete(&self.search_manager, &request).await?;
+        Ok(serde_json::to_value(result)?)
+    }
+    
+    async fn set_log_level(&self, session: &Arc<McpSession>, params: Option<Value>) -> McpResult<Value> {
+        let level = params
+            .as_ref()
+            .and_then(|p| p.get("level"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| McpError::InvalidArguments("Missing level".to_string()))?;
+        let level = logging::LogLevel::parse(level)
+            .ok_or_else(|| McpError::InvalidArguments(format!("Unknown log level: {}", level)))?;
+        
+        logging::set_level(session, level);
+        info!("📝 [{}] Client log level set to {}", session.id(), level);
+        Ok(json!({}))
+    }
+    
+    pub fn error_response(&self, id: Option<Value>, error: McpError) -> JsonRpcResponse {
         JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id,
@@ -175,3 +524,29 @@ impl McpServer {
         }
     }
 }
+
+/// Extract the `uri` parameter from resource requests
+fn resource_uri(params: Option<Value>) -> McpResult<String> {
+    params
+        .as_ref()
+        .and_then(|p| p.get("uri"))
+        .and_then(|uri| uri.as_str())
+        .map(String::from)
+        .ok_or_else(|| McpError::InvalidArguments("Missing uri".to_string()))
+}
+
+/// Single writer for stdout so concurrent responses never interleave
+async fn write_stdout(mut outbound: mpsc::UnboundedReceiver<Value>) -> Result<()> {
+    let mut stdout = tokio::io::stdout();
+    
+    while let Some(message) = outbound.recv().await {
+        let line = serde_json::to_string(&message)?;
+        debug!(target: WIRE_TARGET, "📤 Sending response: {}", line);
+        
+        stdout.write_all(line.as_bytes()).await?;
+        stdout.write_all(b"\n").await?;
+        stdout.flush().await?;
+    }
+    
+    Ok(())
+}
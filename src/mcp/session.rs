@@ -0,0 +1,398 @@
+// MCP Sessions - Per-connection protocol state shared by all transports
+use crate::errors::{McpError, McpErrorResponse, McpResult};
+use crate::mcp::{logging::{self, LogLevel}, types::{ClientInfo, Root}, version::ProtocolVersion};
+use crate::search::ToolNaming;
+use dashmap::{mapref::entry::Entry, DashMap};
+use parking_lot::{Mutex, RwLock};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::AbortHandle;
+
+/// Header used by the Streamable HTTP transport to correlate requests with a session
+pub const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// How long a cancellation for a request that hasn't started yet is remembered
+const EARLY_CANCEL_TTL: Duration = Duration::from_secs(30);
+
+/// State for a single MCP client connection
+///
+/// Every transport (stdio, Streamable HTTP) owns one session per client. Messages
+/// the server wants to push to the client outside of a request/response pair are
+/// queued on the session's outbound channel and drained by the transport. While no
+/// transport stream is attached they are dropped rather than queued.
+pub struct McpSession {
+    id: String,
+    /// Taken by [`McpSession::close`] so an attached stream sees the channel end
+    outbound: Mutex<Option<mpsc::UnboundedSender<Value>>>,
+    outbound_rx: Mutex<Option<mpsc::UnboundedReceiver<Value>>>,
+    last_active: Mutex<Instant>,
+    client_info: RwLock<Option<ClientInfo>>,
+    /// `capabilities` the client declared in `initialize`
+    client_capabilities: RwLock<Value>,
+    protocol_version: RwLock<Option<ProtocolVersion>>,
+    tool_naming: RwLock<ToolNaming>,
+    log_level: RwLock<Option<LogLevel>>,
+    in_flight: DashMap<String, InFlight>,
+    /// Subscribed resource URIs and a hash of the content last seen for each
+    subscriptions: DashMap<String, Option<u64>>,
+    watcher: Mutex<Option<AbortHandle>>,
+    /// Server-to-client requests awaiting a response, by request id
+    pending: DashMap<String, oneshot::Sender<Result<Value, McpErrorResponse>>>,
+    next_request_id: AtomicU64,
+    /// Workspace roots reported by the client via `roots/list`
+    roots: RwLock<Vec<Root>>,
+}
+
+impl McpSession {
+    pub fn new() -> Arc<Self> {
+        let (outbound, outbound_rx) = mpsc::unbounded_channel();
+        Arc::new(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            outbound: Mutex::new(Some(outbound)),
+            outbound_rx: Mutex::new(Some(outbound_rx)),
+            last_active: Mutex::new(Instant::now()),
+            client_info: RwLock::new(None),
+            client_capabilities: RwLock::new(Value::Null),
+            protocol_version: RwLock::new(None),
+            tool_naming: RwLock::new(ToolNaming::default()),
+            log_level: RwLock::new(None),
+            in_flight: DashMap::new(),
+            subscriptions: DashMap::new(),
+            watcher: Mutex::new(None),
+            pending: DashMap::new(),
+            next_request_id: AtomicU64::new(1),
+            roots: RwLock::new(Vec::new()),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Queue a server-to-client message.
+    ///
+    /// Returns false, dropping the message, if no transport stream is attached
+    /// or the session has been closed.
+    pub fn send(&self, message: Value) -> bool {
+        // Hold the receiver slot so a stream can't detach between the check and the send
+        let outbound_rx = self.outbound_rx.lock();
+        if outbound_rx.is_some() {
+            return false;
+        }
+        match self.outbound.lock().as_ref() {
+            Some(outbound) => outbound.send(message).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Take the receiving end of the outbound channel (only one consumer per session)
+    pub fn take_outbound(&self) -> Option<mpsc::UnboundedReceiver<Value>> {
+        self.outbound_rx.lock().take()
+    }
+
+    /// Return a previously taken receiver, e.g. after an SSE client disconnects
+    pub fn restore_outbound(&self, rx: mpsc::UnboundedReceiver<Value>) {
+        *self.outbound_rx.lock() = Some(rx);
+    }
+
+    /// Record client activity, postponing idle expiry
+    pub fn touch(&self) {
+        *self.last_active.lock() = Instant::now();
+    }
+
+    /// Time since the last client activity, or zero while a stream is attached
+    /// or a request is running
+    pub fn idle_for(&self) -> Duration {
+        let running = self.in_flight.iter().any(|entry| matches!(entry.value(), InFlight::Running(_)));
+        if self.outbound_rx.lock().is_none() || running {
+            return Duration::ZERO;
+        }
+        self.last_active.lock().elapsed()
+    }
+
+    /// End the session: close the outbound channel so an attached stream finishes,
+    /// and stop the subscription watcher and any in-flight requests.
+    pub fn close(&self) {
+        self.outbound.lock().take();
+        if let Some(watcher) = self.watcher.lock().take() {
+            watcher.abort();
+        }
+        for entry in self.in_flight.iter() {
+            if let InFlight::Running(handle) = entry.value() {
+                handle.abort();
+            }
+        }
+        self.in_flight.clear();
+    }
+
+    pub fn client_info(&self) -> Option<ClientInfo> {
+        self.client_info.read().clone()
+    }
+
+    pub fn set_client_info(&self, info: ClientInfo) {
+        *self.client_info.write() = Some(info);
+    }
+
+    pub fn set_client_capabilities(&self, capabilities: Value) {
+        *self.client_capabilities.write() = capabilities;
+    }
+
+    /// Whether the client declared a capability (e.g. `roots`, `sampling`) in `initialize`
+    pub fn client_supports(&self, capability: &str) -> bool {
+        self.client_capabilities.read().get(capability).is_some()
+    }
+
+    /// Protocol revision negotiated during `initialize` (the default before that)
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version.read().unwrap_or(ProtocolVersion::DEFAULT)
+    }
+
+    /// Protocol revision negotiated during `initialize`, if it has completed
+    pub fn negotiated_protocol_version(&self) -> Option<ProtocolVersion> {
+        *self.protocol_version.read()
+    }
+
+    pub fn set_protocol_version(&self, version: ProtocolVersion) {
+        *self.protocol_version.write() = Some(version);
+    }
+
+    /// Form in which this client sees tool names (dotted until `initialize` decides)
+    pub fn tool_naming(&self) -> ToolNaming {
+        *self.tool_naming.read()
+    }
+
+    pub fn set_tool_naming(&self, naming: ToolNaming) {
+        *self.tool_naming.write() = naming;
+    }
+
+    /// Minimum level for `notifications/message` (none until `logging/setLevel`)
+    pub fn log_level(&self) -> Option<LogLevel> {
+        *self.log_level.read()
+    }
+
+    pub fn set_log_level(&self, level: LogLevel) {
+        *self.log_level.write() = Some(level);
+    }
+
+    /// Send a request to the client and wait for its response.
+    ///
+    /// The response arrives as an ordinary inbound message and is routed back
+    /// here via [`McpSession::complete_request`].
+    pub async fn request(&self, method: &str, params: Value, timeout: Duration) -> McpResult<Value> {
+        let id = format!("srv-{}", self.next_request_id.fetch_add(1, Ordering::Relaxed));
+        let key = Value::from(id.as_str()).to_string();
+        // An untaken receiver means no transport stream is open to carry the request
+        if self.outbound_rx.lock().is_some() {
+            return Err(McpError::Internal(format!("No open stream to send {} to the client", method)));
+        }
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(key.clone(), tx);
+
+        let sent = self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        }));
+        if !sent {
+            self.pending.remove(&key);
+            return Err(McpError::Internal(format!("Client disconnected before {} could be sent", method)));
+        }
+
+        let response = tokio::time::timeout(timeout, rx).await;
+        self.pending.remove(&key);
+
+        match response {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(McpError::Internal(format!(
+                "Client rejected {} ({}): {}", method, error.code, error.message
+            ))),
+            Ok(Err(_)) => Err(McpError::Internal(format!("Session closed while waiting for {}", method))),
+            Err(_) => Err(McpError::Internal(format!("Client did not answer {} within {:?}", method, timeout))),
+        }
+    }
+
+    /// Deliver a client response to a pending [`McpSession::request`].
+    /// Returns false if no request with that id is waiting.
+    pub fn complete_request(&self, id: &Value, response: Result<Value, McpErrorResponse>) -> bool {
+        match self.pending.remove(&id.to_string()) {
+            Some((_, tx)) => tx.send(response).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn roots(&self) -> Vec<Root> {
+        self.roots.read().clone()
+    }
+
+    pub fn set_roots(&self, roots: Vec<Root>) {
+        *self.roots.write() = roots;
+    }
+
+    /// Track a running request so `notifications/cancelled` can abort it.
+    ///
+    /// The returned guard untracks the request when dropped and aborts the task if
+    /// the caller stops waiting for it (e.g. an HTTP client disconnects). A request
+    /// whose cancellation arrived before it was tracked is aborted straight away.
+    pub fn track_request(&self, request_id: &Value, handle: AbortHandle) -> InFlightRequest<'_> {
+        let key = request_id.to_string();
+        match self.in_flight.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                if matches!(entry.get(), InFlight::Cancelled(_)) {
+                    handle.abort();
+                }
+                entry.insert(InFlight::Running(handle.clone()));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(InFlight::Running(handle.clone()));
+            }
+        }
+        InFlightRequest { session: self, key, handle }
+    }
+
+    /// Abort an in-flight request. Returns false if it isn't running (yet).
+    ///
+    /// Transports dispatch each message on its own task, so a cancellation can be
+    /// handled before the request it refers to is tracked. It is remembered for a
+    /// while in case the request turns up.
+    pub fn cancel_request(&self, request_id: &Value) -> bool {
+        match self.in_flight.entry(request_id.to_string()) {
+            Entry::Occupied(entry) => match entry.get() {
+                InFlight::Running(handle) => {
+                    handle.abort();
+                    entry.remove();
+                    true
+                }
+                InFlight::Cancelled(_) => false,
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(InFlight::Cancelled(Instant::now()));
+                self.in_flight.retain(|_, state| {
+                    !matches!(state, InFlight::Cancelled(at) if at.elapsed() > EARLY_CANCEL_TTL)
+                });
+                false
+            }
+        }
+    }
+
+    /// Subscribe to a resource. `baseline` is the hash of its current content, if known.
+    pub fn subscribe(&self, uri: &str, baseline: Option<u64>) {
+        self.subscriptions.insert(uri.to_string(), baseline);
+    }
+
+    /// Returns false if the session was not subscribed to `uri`
+    pub fn unsubscribe(&self, uri: &str) -> bool {
+        self.subscriptions.remove(uri).is_some()
+    }
+
+    pub fn subscribed_uris(&self) -> Vec<String> {
+        self.subscriptions.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// Record the latest content hash for a subscription.
+    ///
+    /// Returns true if the content changed since it was last seen. The first
+    /// observation only sets the baseline.
+    pub fn observe_resource(&self, uri: &str, hash: u64) -> bool {
+        match self.subscriptions.get_mut(uri) {
+            Some(mut last) => {
+                let changed = matches!(*last, Some(previous) if previous != hash);
+                *last = Some(hash);
+                changed
+            }
+            None => false,
+        }
+    }
+
+    /// Start the subscription watcher unless one is already running
+    pub fn ensure_watcher(&self, spawn: impl FnOnce() -> AbortHandle) {
+        let mut watcher = self.watcher.lock();
+        if watcher.is_none() {
+            *watcher = Some(spawn());
+        }
+    }
+}
+
+impl Drop for McpSession {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.get_mut().take() {
+            watcher.abort();
+        }
+        logging::remove_sink(&self.id);
+    }
+}
+
+/// State of a request id in [`McpSession::cancel_request`]'s bookkeeping
+enum InFlight {
+    Running(AbortHandle),
+    /// Cancelled before it was tracked
+    Cancelled(Instant),
+}
+
+/// Guard for a request registered with [`McpSession::track_request`]
+pub struct InFlightRequest<'a> {
+    session: &'a McpSession,
+    key: String,
+    handle: AbortHandle,
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        self.handle.abort();
+        self.session.in_flight.remove(&self.key);
+    }
+}
+
+/// Registry of live sessions, keyed by session id
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: DashMap<String, Arc<McpSession>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create and register a new session
+    pub fn create(&self) -> Arc<McpSession> {
+        let session = McpSession::new();
+        self.sessions.insert(session.id().to_string(), session.clone());
+        session
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<McpSession>> {
+        self.sessions.get(id).map(|s| s.value().clone())
+    }
+
+    /// Unregister and close a session
+    pub fn remove(&self, id: &str) -> Option<Arc<McpSession>> {
+        let (_, session) = self.sessions.remove(id)?;
+        session.close();
+        Some(session)
+    }
+
+    /// Remove sessions with no attached stream and no activity for `ttl`.
+    /// Returns the ids of the expired sessions.
+    pub fn expire_idle(&self, ttl: Duration) -> Vec<String> {
+        let expired: Vec<String> = self.sessions.iter()
+            .filter(|entry| entry.value().idle_for() >= ttl)
+            .map(|entry| entry.key().clone())
+            .collect();
+        for id in &expired {
+            self.remove(id);
+        }
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
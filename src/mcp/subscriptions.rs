@@ -0,0 +1,85 @@
+// MCP Resource Subscriptions - Change notifications for subscribed resources
+//
+// Backends don't push change events, so each session with subscriptions runs a
+// watcher that polls its resources (e.g. `memory://robot/{id}/context` against
+// the decision engine) and sends `notifications/resources/updated` when the
+// content hash changes.
+use crate::{
+    errors::{McpError, McpResult},
+    mcp::session::McpSession,
+    search::SearchManager,
+};
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Subscribe a session to a resource and make sure its watcher is running
+pub async fn subscribe(
+    search_manager: &Arc<SearchManager>,
+    session: &Arc<McpSession>,
+    uri: &str,
+    poll_interval: Duration,
+) -> McpResult<()> {
+    // Reject URIs no service can resolve; transient backend failures still subscribe
+    let baseline = match search_manager.read_resource(uri).await {
+        Ok(resource) => Some(content_hash(&resource.content)),
+        Err(e @ (McpError::ToolNotFound(_) | McpError::InvalidArguments(_))) => return Err(e),
+        Err(e) => {
+            debug!("[{}] No baseline for {}: {}", session.id(), uri, e);
+            None
+        }
+    };
+
+    session.subscribe(uri, baseline);
+    info!("🔔 [{}] Subscribed to {}", session.id(), uri);
+
+    session.ensure_watcher(|| {
+        let search_manager = search_manager.clone();
+        let session = Arc::downgrade(session);
+        tokio::spawn(watch(search_manager, session, poll_interval)).abort_handle()
+    });
+
+    Ok(())
+}
+
+/// Poll subscribed resources until the session goes away
+async fn watch(search_manager: Arc<SearchManager>, session: Weak<McpSession>, poll_interval: Duration) {
+    let mut ticker = tokio::time::interval(poll_interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+
+        for uri in session.subscribed_uris() {
+            let resource = match search_manager.read_resource(&uri).await {
+                Ok(resource) => resource,
+                Err(e) => {
+                    debug!("[{}] Failed to poll {}: {}", session.id(), uri, e);
+                    continue;
+                }
+            };
+
+            if session.observe_resource(&uri, content_hash(&resource.content)) {
+                debug!("[{}] Resource updated: {}", session.id(), uri);
+                session.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/resources/updated",
+                    "params": { "uri": uri }
+                }));
+            }
+        }
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
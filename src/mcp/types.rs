@@ -27,6 +27,109 @@ pub struct McpTool {
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub input_schema: Option<Value>,
+    /// JSON Schema for `structuredContent` (protocol 2025-06-18+)
+    #[serde(rename = "outputSchema", skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Behavior hints clients use to decide on confirmation (protocol 2025-03-26+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// MCP Tool Annotations
+///
+/// Hints only; clients must not rely on them for security decisions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    /// Annotations for a tool that only reads from our own backends
+    pub fn read_only(title: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            read_only_hint: Some(true),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        }
+    }
+}
+
+/// Workspace root reported by the client (`roots/list`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Root {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Root {
+    /// Repository name for search filters: the root's name, else the last path segment of its URI
+    pub fn repo_name(&self) -> Option<String> {
+        if let Some(name) = self.name.as_deref().filter(|n| !n.is_empty()) {
+            return Some(name.to_string());
+        }
+        self.uri
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|segment| !segment.is_empty() && !segment.ends_with(':'))
+            .map(String::from)
+    }
+}
+
+/// MCP Completion Request (`completion/complete`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteRequest {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+}
+
+/// What is being completed. `ref/tool` is our extension for tool arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+    #[serde(rename = "ref/tool")]
+    Tool { name: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// MCP Completion Result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
 }
 
 /// MCP Tool Call Request
@@ -35,14 +138,20 @@ pub struct ToolCallRequest {
     pub name: String,
     #[serde(default)]
     pub arguments: Value,
+    /// Request metadata (e.g. `progressToken`)
+    #[serde(default, rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 /// MCP Tool Call Result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallResult {
     pub content: Vec<ToolContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Machine-readable result matching the tool's output schema (protocol 2025-06-18+)
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +168,17 @@ impl ToolCallResult {
         Self {
             content: vec![ToolContent::Text { text }],
             is_error: None,
+            structured_content: None,
+        }
+    }
+    
+    /// Structured result with a serialized text copy for clients that only read `content`
+    pub fn structured(value: Value) -> Self {
+        let text = value.to_string();
+        Self {
+            content: vec![ToolContent::Text { text }],
+            is_error: None,
+            structured_content: Some(value),
         }
     }
     
@@ -66,6 +186,53 @@ impl ToolCallResult {
         Self {
             content: vec![ToolContent::Text { text: message }],
             is_error: Some(true),
+            structured_content: None,
+        }
+    }
+}
+
+/// MCP Prompt Definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPrompt {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// Argument accepted by a prompt template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// MCP Prompt Get Request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGetRequest {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: std::collections::HashMap<String, String>,
+}
+
+/// Rendered prompt returned by `prompts/get`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGetResult {
+    pub description: String,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: ToolContent,
+}
+
+impl PromptMessage {
+    pub fn user(text: String) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: ToolContent::Text { text },
         }
     }
 }
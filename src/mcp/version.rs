@@ -0,0 +1,91 @@
+// MCP Protocol Versions - Supported revisions and revision-specific behavior
+use crate::errors::{McpError, McpResult};
+use std::fmt;
+
+/// MCP protocol revisions this server can speak, oldest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    V2024_11_05,
+    V2025_03_26,
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const SUPPORTED: [ProtocolVersion; 3] = [
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+    ];
+
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V2025_06_18;
+
+    /// Assumed for clients that don't send a version (the only revision we used to speak)
+    pub const DEFAULT: ProtocolVersion = ProtocolVersion::V2024_11_05;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    pub fn parse(version: &str) -> Option<Self> {
+        Self::SUPPORTED.into_iter().find(|v| v.as_str() == version)
+    }
+
+    /// Pick the revision to use for a client's requested `protocolVersion`.
+    ///
+    /// A supported revision is echoed back. A newer, unknown revision gets our
+    /// latest so the client can decide whether to continue. Anything older than
+    /// our oldest revision (or not a revision date at all) has no common version.
+    pub fn negotiate(requested: Option<&str>) -> McpResult<Self> {
+        let Some(requested) = requested else {
+            return Ok(Self::DEFAULT);
+        };
+
+        if let Some(version) = Self::parse(requested) {
+            return Ok(version);
+        }
+
+        // Revisions are ISO dates, so they order lexically
+        if is_revision_date(requested) && requested > Self::LATEST.as_str() {
+            return Ok(Self::LATEST);
+        }
+
+        Err(McpError::UnsupportedProtocolVersion {
+            requested: requested.to_string(),
+            supported: Self::SUPPORTED.iter().map(|v| v.as_str().to_string()).collect(),
+        })
+    }
+
+    /// `structuredContent` in tool results and `outputSchema` on tools
+    pub fn supports_structured_output(&self) -> bool {
+        *self >= ProtocolVersion::V2025_06_18
+    }
+
+    /// Tool `annotations` (readOnlyHint, destructiveHint, ...)
+    pub fn supports_tool_annotations(&self) -> bool {
+        *self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// JSON-RPC batches (removed again in 2025-06-18)
+    pub fn supports_batching(&self) -> bool {
+        *self < ProtocolVersion::V2025_06_18
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn is_revision_date(version: &str) -> bool {
+    let bytes = version.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
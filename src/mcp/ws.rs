@@ -0,0 +1,88 @@
+// MCP WebSocket Transport
+//
+// GET /mcp/ws upgrades to a WebSocket that carries JSON-RPC frames in both
+// directions. Each connection is its own MCP session; requests are handled
+// concurrently and every write goes through the session's outbound channel so
+// the socket has a single writer. Upgrades from browser origins not listed in
+// `MCP_ALLOWED_ORIGINS` are rejected with 403.
+use crate::mcp::{http::reject_origin, server::McpServer, session::McpSession};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::{AggregatedMessage, CloseCode, CloseReason};
+use futures::StreamExt;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Register the WebSocket route on an actix app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/mcp/ws", web::get().to(handle_upgrade));
+}
+
+async fn handle_upgrade(
+    req: HttpRequest,
+    body: web::Payload,
+    server: web::Data<McpServer>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if let Some(resp) = reject_origin(&req, &server) {
+        return Ok(resp);
+    }
+
+    let (response, ws, stream) = actix_ws::handle(&req, body)?;
+
+    let server = server.get_ref().clone();
+    actix_web::rt::spawn(run_connection(server, ws, stream));
+
+    Ok(response)
+}
+
+async fn run_connection(server: McpServer, mut ws: actix_ws::Session, stream: actix_ws::MessageStream) {
+    // Fragmented messages arrive as continuation frames; hand them on whole
+    let mut stream = stream.aggregate_continuations();
+    let session = McpSession::new();
+    let mut outbound = session
+        .take_outbound()
+        .expect("fresh session always has an outbound channel");
+
+    info!("🔌 [{}] WebSocket session opened", session.id());
+
+    let close_reason = loop {
+        tokio::select! {
+            frame = stream.next() => match frame {
+                Some(Ok(AggregatedMessage::Text(text))) => {
+                    dispatch(server.clone(), session.clone(), text.to_string());
+                }
+                Some(Ok(AggregatedMessage::Ping(bytes))) => {
+                    if ws.pong(&bytes).await.is_err() {
+                        break None;
+                    }
+                }
+                Some(Ok(AggregatedMessage::Binary(_))) => {
+                    warn!("[{}] Ignoring binary WebSocket frame", session.id());
+                }
+                Some(Ok(AggregatedMessage::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    error!("❌ [{}] WebSocket protocol error: {}", session.id(), e);
+                    break Some(CloseReason::from(CloseCode::Protocol));
+                }
+                None => break None,
+            },
+            Some(message) = outbound.recv() => {
+                if ws.text(message.to_string()).await.is_err() {
+                    break None;
+                }
+            }
+        }
+    };
+
+    // Stop in-flight tool calls and the subscription watcher with the socket
+    session.close();
+    info!("🔌 [{}] WebSocket session closed", session.id());
+    let _ = ws.close(close_reason).await;
+}
+
+/// Handle one inbound frame on its own task; the response is queued on the session
+fn dispatch(server: McpServer, session: Arc<McpSession>, text: String) {
+    actix_web::rt::spawn(async move {
+        server.respond(&session, &text).await;
+    });
+}
@@ -1,9 +1,9 @@
 // Embeddings Search Service - Direct access to embeddings service
-use crate::{search::*, mcp::McpTool, errors::{McpError, McpResult}};
+use crate::{search::*, mcp::{McpTool, ToolAnnotations, ToolContext}, errors::{McpError, McpResult}};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use super::service_trait::SearchService;
+use super::service_trait::{check_health, SearchService};
 
 pub struct EmbeddingsService {
     base_url: String,
@@ -17,6 +17,25 @@ impl EmbeddingsService {
             client: reqwest::Client::new(),
         }
     }
+    
+    /// Embed a batch of texts in a single request
+    async fn batch_embed(&self, texts: Vec<String>) -> McpResult<BatchEmbedResponse> {
+        let response = self.client
+            .post(format!("{}/batch/embed", self.base_url))
+            .json(&BatchEmbedRequest { texts })
+            .send()
+            .await
+            .map_err(|e| McpError::Internal(format!("Embeddings request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            return Err(McpError::Internal(format!(
+                "Embeddings service returned {}", response.status()
+            )));
+        }
+        
+        response.json().await
+            .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -64,6 +83,19 @@ impl SearchService for EmbeddingsService {
                     },
                     "required": ["text"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "embedding": {
+                            "type": "array",
+                            "items": { "type": "number" }
+                        },
+                        "dimension": { "type": "integer" },
+                        "model": { "type": "string" }
+                    },
+                    "required": ["embedding", "dimension", "model"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Embed Text")),
             },
             McpTool {
                 name: "embeddings.batch_embed".to_string(),
@@ -79,6 +111,23 @@ impl SearchService for EmbeddingsService {
                     },
                     "required": ["texts"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "embeddings": {
+                            "type": "array",
+                            "items": {
+                                "type": "array",
+                                "items": { "type": "number" }
+                            }
+                        },
+                        "dimension": { "type": "integer" },
+                        "model": { "type": "string" },
+                        "count": { "type": "integer" }
+                    },
+                    "required": ["embeddings", "count"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Batch Embed Texts")),
             },
             McpTool {
                 name: "embeddings.similarity".to_string(),
@@ -91,11 +140,29 @@ impl SearchService for EmbeddingsService {
                     },
                     "required": ["text1", "text2"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "similarity": { "type": "number" },
+                        "text1": { "type": "string" },
+                        "text2": { "type": "string" }
+                    },
+                    "required": ["similarity"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Text Similarity")),
             },
         ]
     }
     
-    async fn call_tool(&self, tool: &str, args: Value) -> McpResult<Value> {
+    fn backend(&self) -> Option<&'static str> {
+        Some("embeddings")
+    }
+    
+    async fn health_check(&self) -> McpResult<()> {
+        check_health(&self.client, &format!("{}/health", self.base_url)).await
+    }
+    
+    async fn call_tool(&self, tool: &str, args: Value, ctx: &ToolContext) -> McpResult<Value> {
         match tool {
             "embed" => {
                 let text = args.get("text")
@@ -130,27 +197,16 @@ impl SearchService for EmbeddingsService {
                     .and_then(|v| serde_json::from_value(v.clone()).ok())
                     .ok_or_else(|| McpError::InvalidArguments("Missing 'texts' argument".into()))?;
                 
-                let response = self.client
-                    .post(format!("{}/batch/embed", self.base_url))
-                    .json(&BatchEmbedRequest { texts })
-                    .send()
-                    .await
-                    .map_err(|e| McpError::Internal(format!("Embeddings request failed: {}", e)))?;
-                
-                if !response.status().is_success() {
-                    return Err(McpError::Internal(format!(
-                        "Embeddings service returned {}", response.status()
-                    )));
-                }
-                
-                let result: BatchEmbedResponse = response.json().await
-                    .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
+                let total = texts.len() as f64;
+                ctx.progress(0.0, Some(total), format!("Embedding {} texts", texts.len()));
+                let result = self.batch_embed(texts).await?;
+                ctx.progress(total, Some(total), format!("Embedded {} texts", result.embeddings.len()));
                 
                 Ok(json!({
+                    "count": result.embeddings.len(),
                     "embeddings": result.embeddings,
                     "dimension": result.dimension,
-                    "model": result.model,
-                    "count": result.embeddings.len()
+                    "model": result.model
                 }))
             }
             
@@ -163,23 +219,7 @@ impl SearchService for EmbeddingsService {
                     .ok_or_else(|| McpError::InvalidArguments("Missing 'text2' argument".into()))?;
                 
                 // Get embeddings for both texts
-                let response = self.client
-                    .post(format!("{}/batch/embed", self.base_url))
-                    .json(&BatchEmbedRequest { 
-                        texts: vec![text1.to_string(), text2.to_string()] 
-                    })
-                    .send()
-                    .await
-                    .map_err(|e| McpError::Internal(format!("Embeddings request failed: {}", e)))?;
-                
-                if !response.status().is_success() {
-                    return Err(McpError::Internal(format!(
-                        "Embeddings service returned {}", response.status()
-                    )));
-                }
-                
-                let result: BatchEmbedResponse = response.json().await
-                    .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
+                let result = self.batch_embed(vec![text1.to_string(), text2.to_string()]).await?;
                 
                 if result.embeddings.len() != 2 {
                     return Err(McpError::Internal("Expected 2 embeddings".into()));
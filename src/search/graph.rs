@@ -1,9 +1,9 @@
 // Graph Search Service - Direct access to knowledge graph knowledge layer
-use crate::{search::*, mcp::McpTool, errors::{McpError, McpResult}};
+use crate::{search::*, mcp::{McpTool, ToolAnnotations, ToolContext}, errors::{McpError, McpResult}};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use super::service_trait::SearchService;
+use super::service_trait::{check_health, SearchService};
 
 pub struct GraphSearchService {
     base_url: String,
@@ -17,8 +17,126 @@ impl GraphSearchService {
             client: reqwest::Client::new(),
         }
     }
+    
+    /// Fetch a single entity
+    async fn fetch_entity(&self, entity_id: &str) -> McpResult<Value> {
+        let response = self.client
+            .get(format!("{}/api/graph/entities/{}", self.base_url, entity_id))
+            .send()
+            .await
+            .map_err(|e| McpError::Internal(format!("Get entity request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            return Err(McpError::Internal(format!(
+                "Relation graph returned {}", response.status()
+            )));
+        }
+        
+        response.json().await
+            .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))
+    }
+    
+    /// Fetch an entity together with its neighbors up to `depth` hops away
+    async fn fetch_neighbors(&self, entity_id: &str, depth: u64) -> McpResult<Value> {
+        let response = self.client
+            .get(format!("{}/api/graph/entities/{}/neighbors", self.base_url, entity_id))
+            .query(&[("depth", depth.to_string())])
+            .send()
+            .await
+            .map_err(|e| McpError::Internal(format!("Traverse request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            return Err(McpError::Internal(format!(
+                "Relation graph returned {}", response.status()
+            )));
+        }
+        
+        response.json().await
+            .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))
+    }
+    
+    /// Fetch the ontologies (entity type schemas): a bare list, or `{ "ontologies": [...] }`
+    /// from newer graph versions
+    async fn fetch_ontologies(&self) -> McpResult<Value> {
+        let response = self.client
+            .get(format!("{}/api/ontology", self.base_url))
+            .send()
+            .await
+            .map_err(|e| McpError::Internal(format!("List ontologies request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            return Err(McpError::Internal(format!(
+                "Relation graph returned {}", response.status()
+            )));
+        }
+        
+        response.json().await
+            .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))
+    }
+    
+    /// Entity IDs starting with `prefix` (any ID for an empty prefix), from the
+    /// graph's entity listing
+    async fn complete_entity_ids(&self, prefix: &str) -> McpResult<Vec<String>> {
+        let response = self.client
+            .get(format!("{}/api/graph/entities", self.base_url))
+            .query(&[("prefix", prefix.to_string()), ("limit", COMPLETION_LIMIT.to_string())])
+            .send()
+            .await
+            .map_err(|e| McpError::Internal(format!("Entity completion request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            return Err(McpError::Internal(format!(
+                "Relation graph returned {}", response.status()
+            )));
+        }
+        
+        let result: Value = response.json().await
+            .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
+        
+        // A bare list or `{ "entities": [...] }`, of IDs or entity objects
+        let entities = match &result {
+            Value::Array(entities) => Some(entities),
+            wrapped => wrapped.get("entities").and_then(|v| v.as_array()),
+        };
+        
+        Ok(entities.into_iter().flatten().filter_map(|entity| names(entity, "id")).collect())
+    }
+    
+    /// Ontology names, or the entity/relationship type names they declare
+    async fn complete_from_ontologies(&self, field: Option<&str>) -> McpResult<Vec<String>> {
+        let ontologies = self.fetch_ontologies().await?;
+        
+        let ontologies = match &ontologies {
+            Value::Array(ontologies) => Some(ontologies),
+            wrapped => wrapped.get("ontologies").and_then(|v| v.as_array()),
+        };
+        
+        let mut values = Vec::new();
+        for ontology in ontologies.into_iter().flatten() {
+            match field {
+                None => values.extend(names(ontology, "name")),
+                Some(field) => {
+                    for entry in ontology.get(field).and_then(|v| v.as_array()).into_iter().flatten() {
+                        values.extend(names(entry, "name"));
+                    }
+                }
+            }
+        }
+        
+        Ok(values)
+    }
+}
+
+/// A plain string, or the string under `key` of an object
+fn names(value: &Value, key: &str) -> Option<String> {
+    value.as_str()
+        .or_else(|| value.get(key).and_then(|v| v.as_str()))
+        .map(String::from)
 }
 
+/// How many entity IDs to ask the graph for when completing
+const COMPLETION_LIMIT: usize = 100;
+
 #[derive(Debug, Deserialize)]
 struct SearchResult {
     chunks: Vec<Value>,
@@ -57,11 +175,39 @@ impl SearchService for GraphSearchService {
                         "source_types": {
                             "type": "array",
                             "items": { "type": "string" },
-                            "description": "Filter by source type (code, documentation, etc.)"
+                            "description": "Filter by source type (code, docs, chat, tickets)"
+                        },
+                        "ontology": {
+                            "type": "string",
+                            "description": "Optional: Only search entities of this ontology"
+                        },
+                        "entity_types": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Optional: Filter entities by type (Function, Service, etc.)"
+                        },
+                        "repos": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Optional: Filter by repository names (default: the client's workspace roots; [] searches everything)"
                         }
                     },
                     "required": ["query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "chunks": {
+                            "type": "array",
+                            "items": { "type": "object" }
+                        },
+                        "entities": {
+                            "type": "array",
+                            "items": { "type": "object" }
+                        }
+                    }
+                })),
+                annotations: Some(ToolAnnotations::read_only("Knowledge Graph Search")),
             },
             McpTool {
                 name: "graph.traverse".to_string(),
@@ -86,6 +232,17 @@ impl SearchService for GraphSearchService {
                     },
                     "required": ["entity_id"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "entity": { "type": "object" },
+                        "neighbors": {
+                            "type": "array",
+                            "items": { "type": "object" }
+                        }
+                    }
+                })),
+                annotations: Some(ToolAnnotations::read_only("Traverse Knowledge Graph")),
             },
             McpTool {
                 name: "graph.get_entity".to_string(),
@@ -100,6 +257,11 @@ impl SearchService for GraphSearchService {
                     },
                     "required": ["entity_id"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "description": "Entity record as stored in the knowledge graph"
+                })),
+                annotations: Some(ToolAnnotations::read_only("Get Entity")),
             },
             McpTool {
                 name: "graph.list_ontologies".to_string(),
@@ -108,6 +270,17 @@ impl SearchService for GraphSearchService {
                     "type": "object",
                     "properties": {}
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "ontologies": {
+                            "type": "array",
+                            "items": { "type": "object" }
+                        }
+                    },
+                    "required": ["ontologies"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("List Ontologies")),
             },
             McpTool {
                 name: "graph.statistics".to_string(),
@@ -116,11 +289,24 @@ impl SearchService for GraphSearchService {
                     "type": "object",
                     "properties": {}
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "description": "Knowledge graph counters (entities, relationships, ...)"
+                })),
+                annotations: Some(ToolAnnotations::read_only("Knowledge Graph Statistics")),
             },
         ]
     }
     
-    async fn call_tool(&self, tool: &str, args: Value) -> McpResult<Value> {
+    fn backend(&self) -> Option<&'static str> {
+        Some("graph")
+    }
+    
+    async fn health_check(&self) -> McpResult<()> {
+        check_health(&self.client, &format!("{}/health", self.base_url)).await
+    }
+    
+    async fn call_tool(&self, tool: &str, args: Value, _ctx: &ToolContext) -> McpResult<Value> {
         match tool {
             "search" => {
                 let query = args.get("query")
@@ -131,13 +317,24 @@ impl SearchService for GraphSearchService {
                     .and_then(|v| v.as_u64())
                     .unwrap_or(10) as usize;
                 
+                let mut body = json!({
+                    "query": query,
+                    "limit": limit,
+                    "include_entities": true
+                });
+                if let Some(repos) = args.get("repos").filter(|r| r.as_array().is_some_and(|r| !r.is_empty())) {
+                    body["repos"] = repos.clone();
+                }
+                if let Some(ontology) = args.get("ontology").and_then(|v| v.as_str()) {
+                    body["ontology"] = json!(ontology);
+                }
+                if let Some(entity_types) = args.get("entity_types").filter(|t| t.as_array().is_some_and(|t| !t.is_empty())) {
+                    body["entity_types"] = entity_types.clone();
+                }
+                
                 let response = self.client
                     .post(format!("{}/api/search", self.base_url))
-                    .json(&json!({
-                        "query": query,
-                        "limit": limit,
-                        "include_entities": true
-                    }))
+                    .json(&body)
                     .send()
                     .await
                     .map_err(|e| McpError::Internal(format!("Search request failed: {}", e)))?;
@@ -164,23 +361,7 @@ impl SearchService for GraphSearchService {
                     .unwrap_or(2);
                 
                 // Get entity and neighbors
-                let response = self.client
-                    .get(format!("{}/api/graph/entities/{}/neighbors", self.base_url, entity_id))
-                    .query(&[("depth", depth.to_string())])
-                    .send()
-                    .await
-                    .map_err(|e| McpError::Internal(format!("Traverse request failed: {}", e)))?;
-                
-                if !response.status().is_success() {
-                    return Err(McpError::Internal(format!(
-                        "Relation graph returned {}", response.status()
-                    )));
-                }
-                
-                let result: Value = response.json().await
-                    .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
-                
-                Ok(result)
+                self.fetch_neighbors(entity_id, depth).await
             }
             
             "get_entity" => {
@@ -188,42 +369,10 @@ impl SearchService for GraphSearchService {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| McpError::InvalidArguments("Missing 'entity_id' argument".into()))?;
                 
-                let response = self.client
-                    .get(format!("{}/api/graph/entities/{}", self.base_url, entity_id))
-                    .send()
-                    .await
-                    .map_err(|e| McpError::Internal(format!("Get entity request failed: {}", e)))?;
-                
-                if !response.status().is_success() {
-                    return Err(McpError::Internal(format!(
-                        "Relation graph returned {}", response.status()
-                    )));
-                }
-                
-                let result: Value = response.json().await
-                    .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
-                
-                Ok(result)
+                self.fetch_entity(entity_id).await
             }
             
-            "list_ontologies" => {
-                let response = self.client
-                    .get(format!("{}/api/ontology", self.base_url))
-                    .send()
-                    .await
-                    .map_err(|e| McpError::Internal(format!("List ontologies request failed: {}", e)))?;
-                
-                if !response.status().is_success() {
-                    return Err(McpError::Internal(format!(
-                        "Relation graph returned {}", response.status()
-                    )));
-                }
-                
-                let result: Value = response.json().await
-                    .map_err(|e| McpError::Internal(format!("Failed to parse response: {}", e)))?;
-                
-                Ok(result)
-            }
+            "list_ontologies" => self.fetch_ontologies().await,
             
             "statistics" => {
                 let response = self.client
@@ -247,4 +396,63 @@ impl SearchService for GraphSearchService {
             _ => Err(McpError::ToolNotFound(format!("Unknown tool: graph.{}", tool))),
         }
     }
+    
+    fn list_resource_templates(&self) -> Vec<ResourceTemplate> {
+        vec![
+            ResourceTemplate {
+                uri_template: "graph://entity/{id}".to_string(),
+                name: "Knowledge graph entity".to_string(),
+                description: Some("Details of a single entity in the knowledge graph".to_string()),
+                mime_type: Some("application/json".to_string()),
+            },
+            ResourceTemplate {
+                uri_template: "graph://entity/{id}/neighbors?depth={n}".to_string(),
+                name: "Knowledge graph neighborhood".to_string(),
+                description: Some("An entity and its neighbors up to n hops away".to_string()),
+                mime_type: Some("application/json".to_string()),
+            },
+        ]
+    }
+    
+    async fn complete_argument(&self, argument: &str, prefix: &str) -> McpResult<Vec<String>> {
+        match argument {
+            "entity_id" => self.complete_entity_ids(prefix).await,
+            "ontology" => self.complete_from_ontologies(None).await,
+            "entity_types" => self.complete_from_ontologies(Some("entity_types")).await,
+            "relationship_types" => self.complete_from_ontologies(Some("relationship_types")).await,
+            "source_types" => Ok(SOURCE_TYPES.iter().map(|s| s.to_string()).collect()),
+            _ => Ok(vec![]),
+        }
+    }
+    
+    async fn read_resource(&self, uri: &str) -> McpResult<ResourceContent> {
+        let parsed = reqwest::Url::parse(uri)
+            .map_err(|e| McpError::InvalidArguments(format!("Invalid resource URI {}: {}", uri, e)))?;
+        
+        if parsed.host_str() != Some("entity") {
+            return Err(McpError::ToolNotFound(format!("Unknown resource: {}", uri)));
+        }
+        
+        let segments: Vec<&str> = parsed.path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        
+        let content = match segments.as_slice() {
+            [entity_id] => self.fetch_entity(entity_id).await?,
+            [entity_id, "neighbors"] => {
+                let depth = match parsed.query_pairs().find(|(k, _)| k == "depth") {
+                    Some((_, depth)) => depth.parse()
+                        .map_err(|_| McpError::InvalidArguments(format!("Invalid depth in {}", uri)))?,
+                    None => 1,
+                };
+                self.fetch_neighbors(entity_id, depth).await?
+            }
+            _ => return Err(McpError::ToolNotFound(format!("Unknown resource: {}", uri))),
+        };
+        
+        Ok(ResourceContent {
+            content: content.to_string(),
+            mime_type: Some("application/json".to_string()),
+        })
+    }
 }
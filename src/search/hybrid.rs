@@ -23,11 +23,12 @@
 //! Graph adds: config.rs (imports), auth_test.rs (tests), auth.md (docs)
 //! Result: Complete context for the AI agent
 
-use crate::{mcp::McpTool, errors::{McpError, McpResult}};
+use crate::{mcp::{McpTool, ToolAnnotations, ToolContext}, errors::{McpError, McpResult}};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use tracing::warn;
 use super::service_trait::SearchService;
 
 /// Configuration for hybrid search ranking
@@ -57,6 +58,9 @@ impl Default for RankingWeights {
     }
 }
 
+/// Token budget for an LLM query expansion
+const EXPANSION_MAX_TOKENS: u32 = 300;
+
 /// Hybrid Search Service - Combines embeddings and graph search
 pub struct HybridSearchService {
     embeddings_url: String,
@@ -85,8 +89,11 @@ impl HybridSearchService {
         }
     }
     
-    /// Expand query using LLM for semantic enhancement
-    async fn expand_query(&self, query: &str) -> McpResult<ExpandedQuery> {
+    /// Expand query using LLM for semantic enhancement.
+    ///
+    /// Prefers the client's own model via MCP sampling, then the local Ollama
+    /// model, and falls back to the original query if neither answers.
+    async fn expand_query(&self, query: &str, ctx: &ToolContext) -> ExpandedQuery {
         let prompt = format!(
             r#"You are a code search assistant. Given a user query, expand it with:
 1. Semantically similar programming terms
@@ -104,73 +111,101 @@ Respond in JSON format:
             query
         );
         
-        let response = self.client
-            .post(format!("{}/api/generate", self.ollama_url))
-            .json(&json!({
-                "model": self.expansion_model,
-                "prompt": prompt,
-                "stream": false
-            }))
-            .send()
-            .await
-            .map_err(|e| McpError::Internal(format!("LLM request failed: {}", e)))?;
-        
-        if !response.status().is_success() {
-            // Fallback to original query if LLM fails
-            return Ok(ExpandedQuery {
-                original: query.to_string(),
-                semantic_terms: Vec::new(),
-                technical_concepts: Vec::new(),
-                potential_names: Vec::new(),
-                combined: query.to_string(),
-            });
-        }
-        
-        let result: Value = response.json().await
-            .map_err(|e| McpError::Internal(format!("Failed to parse LLM response: {}", e)))?;
-        
-        // Parse LLM response
-        let llm_response = result.get("response")
-            .and_then(|v| v.as_str())
-            .unwrap_or("{}");
+        let llm_response = match ctx.sample(&prompt, EXPANSION_MAX_TOKENS).await {
+            Some(Ok(text)) => Some(text),
+            Some(Err(e)) => {
+                warn!("Query expansion via client sampling failed ({}); trying Ollama", e);
+                self.ollama_generate(&prompt).await
+            }
+            None => self.ollama_generate(&prompt).await,
+        };
         
-        // Try to extract JSON from response
-        let expansion: ExpansionResponse = serde_json::from_str(llm_response)
-            .unwrap_or_default();
+        let expansion = match llm_response {
+            // Models often wrap the JSON in prose or code fences
+            Some(text) => serde_json::from_str(extract_json(&text))
+                .unwrap_or_else(|e| {
+                    warn!("Query expansion returned invalid JSON ({}); searching with the original query", e);
+                    ExpansionResponse::default()
+                }),
+            None => ExpansionResponse::default(),
+        };
         
         // Combine all terms into expanded query
         let mut all_terms = vec![query.to_string()];
         all_terms.extend(expansion.semantic_terms.clone());
         all_terms.extend(expansion.technical_concepts.clone());
         
-        Ok(ExpandedQuery {
+        ExpandedQuery {
             original: query.to_string(),
             semantic_terms: expansion.semantic_terms,
             technical_concepts: expansion.technical_concepts,
             potential_names: expansion.potential_names,
             combined: all_terms.join(" "),
-        })
+        }
     }
     
-    /// Perform vector search via embeddings service
-    async fn vector_search(&self, query: &str, limit: usize) -> McpResult<Vec<SearchResult>> {
+    /// Generate a completion with the local Ollama model. Returns `None` (after
+    /// logging why) if Ollama is unreachable or errors.
+    async fn ollama_generate(&self, prompt: &str) -> Option<String> {
         let response = self.client
-            .post(format!("{}/api/v1/search", self.embeddings_url))
+            .post(format!("{}/api/generate", self.ollama_url))
             .json(&json!({
-                "query": query,
-                "limit": limit,
-                "include_content": true
+                "model": self.expansion_model,
+                "prompt": prompt,
+                "stream": false
             }))
             .send()
+            .await;
+        
+        let response = match response {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                warn!("Query expansion failed: Ollama returned {}; searching with the original query", response.status());
+                return None;
+            }
+            Err(e) => {
+                warn!("Query expansion failed: Ollama unreachable ({}); searching with the original query", e);
+                return None;
+            }
+        };
+        
+        match response.json::<Value>().await {
+            Ok(result) => result.get("response").and_then(|v| v.as_str()).map(String::from),
+            Err(e) => {
+                warn!("Query expansion failed: invalid Ollama response ({}); searching with the original query", e);
+                None
+            }
+        }
+    }
+    
+    /// Perform vector search via embeddings service
+    async fn vector_search(&self, query: &str, limit: usize, repos: &[String]) -> McpResult<Vec<SearchResult>> {
+        let mut body = json!({
+            "query": query,
+            "limit": limit,
+            "include_content": true
+        });
+        if !repos.is_empty() {
+            body["repos"] = json!(repos);
+        }
+        
+        let response = self.client
+            .post(format!("{}/api/v1/search", self.embeddings_url))
+            .json(&body)
+            .send()
             .await
             .map_err(|e| McpError::Internal(format!("Vector search failed: {}", e)))?;
         
         if !response.status().is_success() {
+            warn!("Vector search returned {}; continuing without vector results", response.status());
             return Ok(Vec::new());
         }
         
         let result: VectorSearchResponse = response.json().await
-            .unwrap_or_default();
+            .unwrap_or_else(|e| {
+                warn!("Failed to parse vector search response: {}", e);
+                VectorSearchResponse::default()
+            });
         
         Ok(result.results.into_iter().map(|r| SearchResult {
             id: r.id,
@@ -189,24 +224,33 @@ Respond in JSON format:
     }
     
     /// Perform graph search via relation-graph service
-    async fn graph_search(&self, query: &str, limit: usize) -> McpResult<Vec<SearchResult>> {
+    async fn graph_search(&self, query: &str, limit: usize, repos: &[String]) -> McpResult<Vec<SearchResult>> {
+        let mut body = json!({
+            "query": query,
+            "limit": limit,
+            "include_entities": true
+        });
+        if !repos.is_empty() {
+            body["repos"] = json!(repos);
+        }
+        
         let response = self.client
             .post(format!("{}/api/search", self.graph_url))
-            .json(&json!({
-                "query": query,
-                "limit": limit,
-                "include_entities": true
-            }))
+            .json(&body)
             .send()
             .await
             .map_err(|e| McpError::Internal(format!("Graph search failed: {}", e)))?;
         
         if !response.status().is_success() {
+            warn!("Graph search returned {}; continuing without graph results", response.status());
             return Ok(Vec::new());
         }
         
         let result: GraphSearchResponse = response.json().await
-            .unwrap_or_default();
+            .unwrap_or_else(|e| {
+                warn!("Failed to parse graph search response: {}", e);
+                GraphSearchResponse::default()
+            });
         
         Ok(result.entities.unwrap_or_default().into_iter().map(|e| SearchResult {
             id: e.id.clone(),
@@ -236,8 +280,14 @@ Respond in JSON format:
                 .await
                 .map_err(|e| McpError::Internal(format!("Related search failed: {}", e)))?;
             
-            if response.status().is_success() {
-                if let Ok(result) = response.json::<RelatedResponse>().await {
+            if !response.status().is_success() {
+                warn!("Neighbors of {} returned {}; skipping", entity_id, response.status());
+                continue;
+            }
+            
+            match response.json::<RelatedResponse>().await {
+                Err(e) => warn!("Failed to parse neighbors of {}: {}", entity_id, e),
+                Ok(result) => {
                     for neighbor in result.neighbors.unwrap_or_default() {
                         all_related.push(SearchResult {
                             id: neighbor.id.clone(),
@@ -360,6 +410,29 @@ struct ExpansionResponse {
     potential_names: Vec<String>,
 }
 
+/// Unranked hits from one search modality, as sent in a partial result
+fn partial_hits(hits: &[SearchResult]) -> Value {
+    json!({
+        "results": hits.iter().map(|r| json!({
+            "id": r.id,
+            "title": r.title,
+            "path": r.path,
+            "content_type": r.content_type,
+            "semantic_score": r.semantic_score,
+            "graph_score": r.graph_score,
+            "source": r.source
+        })).collect::<Vec<_>>()
+    })
+}
+
+/// The outermost `{...}` in an LLM answer, or the whole answer if there is none
+fn extract_json(text: &str) -> &str {
+    match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &text[start..=end],
+        _ => text,
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ExpandedQuery {
     original: String,
@@ -499,10 +572,65 @@ impl SearchService for HybridSearchService {
                             "type": "boolean",
                             "description": "Include graph-related entities (default: true)",
                             "default": true
+                        },
+                        "repos": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Optional: Filter by repository names (default: the client's workspace roots; [] searches everything)"
                         }
                     },
                     "required": ["query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "total_results": { "type": "integer" },
+                        "vector_matches": { "type": "integer" },
+                        "graph_matches": { "type": "integer" },
+                        "context_bundle": {
+                            "type": "object",
+                            "properties": {
+                                "query": { "type": "string" },
+                                "items": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "id": { "type": "string" },
+                                            "title": { "type": "string" },
+                                            "content": { "type": "string" },
+                                            "path": { "type": ["string", "null"] },
+                                            "content_type": { "type": "string" },
+                                            "relevance_score": { "type": "number" },
+                                            "tokens": { "type": "integer" }
+                                        }
+                                    }
+                                },
+                                "total_tokens": { "type": "integer" },
+                                "context_window": { "type": "integer" }
+                            }
+                        },
+                        "results": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "string" },
+                                    "title": { "type": "string" },
+                                    "path": { "type": ["string", "null"] },
+                                    "content_type": { "type": "string" },
+                                    "relevance_score": { "type": "number" },
+                                    "semantic_score": { "type": "number" },
+                                    "graph_score": { "type": "number" },
+                                    "source": { "type": "string" }
+                                }
+                            }
+                        }
+                    },
+                    "required": ["query", "total_results", "results"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Hybrid Context Search")),
             },
             McpTool {
                 name: "context.expand".to_string(),
@@ -517,6 +645,27 @@ impl SearchService for HybridSearchService {
                     },
                     "required": ["query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "original": { "type": "string" },
+                        "semantic_terms": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "technical_concepts": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "potential_names": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "combined": { "type": "string" }
+                    },
+                    "required": ["original", "combined"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Expand Query")),
             },
             McpTool {
                 name: "context.related".to_string(),
@@ -536,11 +685,34 @@ impl SearchService for HybridSearchService {
                     },
                     "required": ["entity_id"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": { "type": "string" },
+                        "depth": { "type": "integer" },
+                        "related_count": { "type": "integer" },
+                        "related": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "string" },
+                                    "title": { "type": "string" },
+                                    "path": { "type": ["string", "null"] },
+                                    "content_type": { "type": "string" },
+                                    "graph_score": { "type": "number" }
+                                }
+                            }
+                        }
+                    },
+                    "required": ["entity_id", "related"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Related Entities")),
             },
         ]
     }
     
-    async fn call_tool(&self, tool: &str, args: Value) -> McpResult<Value> {
+    async fn call_tool(&self, tool: &str, args: Value, ctx: &ToolContext) -> McpResult<Value> {
         match tool {
             "search" => {
                 let query = args.get("query")
@@ -563,27 +735,56 @@ impl SearchService for HybridSearchService {
                     .and_then(|v| v.as_bool())
                     .unwrap_or(true);
                 
+                let repos: Vec<String> = args.get("repos")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+                
+                // Stages reported to the client as progress
+                const STAGES: f64 = 4.0;
+                
                 // 1. Optionally expand the query
+                ctx.progress(0.0, Some(STAGES), "Expanding query");
                 let search_query = if expand_query {
-                    let expanded = self.expand_query(query).await?;
-                    expanded.combined
+                    self.expand_query(query, ctx).await.combined
                 } else {
                     query.to_string()
                 };
                 
                 // 2. Run parallel vector + graph search
+                ctx.progress(1.0, Some(STAGES), "Running vector and graph search");
+                // Streaming callers get each side's hits as soon as it finishes
                 let (vector_results, graph_results) = tokio::join!(
-                    self.vector_search(&search_query, limit * 2),
-                    self.graph_search(&search_query, limit * 2)
+                    async {
+                        let results = self.vector_search(&search_query, limit * 2, &repos).await;
+                        if let Ok(hits) = &results {
+                            ctx.partial("vector", partial_hits(hits));
+                        }
+                        results
+                    },
+                    async {
+                        let results = self.graph_search(&search_query, limit * 2, &repos).await;
+                        if let Ok(hits) = &results {
+                            ctx.partial("graph", partial_hits(hits));
+                        }
+                        results
+                    }
                 );
                 
-                let vector_results = vector_results.unwrap_or_default();
-                let graph_results = graph_results.unwrap_or_default();
+                let vector_results = vector_results.unwrap_or_else(|e| {
+                    warn!("{}; continuing with graph results only", e);
+                    Vec::new()
+                });
+                let graph_results = graph_results.unwrap_or_else(|e| {
+                    warn!("{}; continuing with vector results only", e);
+                    Vec::new()
+                });
                 
                 // 3. Merge and rank results
                 let mut ranked = self.merge_and_rank(vector_results.clone(), graph_results.clone());
                 
                 // 4. Optionally fetch related entities
+                ctx.progress(2.0, Some(STAGES), "Fetching related entities");
                 if include_related && !ranked.is_empty() {
                     let entity_ids: Vec<String> = ranked.iter()
                         .filter_map(|r| r.entity_id.clone())
@@ -591,26 +792,32 @@ impl SearchService for HybridSearchService {
                         .collect();
                     
                     if !entity_ids.is_empty() {
-                        if let Ok(related) = self.get_related(&entity_ids, 1).await {
-                            // Add related with lower scores
-                            let mut related_scored: Vec<SearchResult> = related.into_iter()
-                                .map(|mut r| {
-                                    r.final_score = 0.3;  // Lower than direct matches
-                                    r
-                                })
-                                .collect();
-                            ranked.append(&mut related_scored);
+                        match self.get_related(&entity_ids, 1).await {
+                            Ok(related) => {
+                                // Add related with lower scores
+                                let mut related_scored: Vec<SearchResult> = related.into_iter()
+                                    .map(|mut r| {
+                                        r.final_score = 0.3;  // Lower than direct matches
+                                        r
+                                    })
+                                    .collect();
+                                ranked.append(&mut related_scored);
+                            }
+                            Err(e) => warn!("{}; returning results without related entities", e),
                         }
                     }
                 }
                 
                 // 5. Assemble context bundle
+                ctx.progress(3.0, Some(STAGES), "Assembling context");
                 let context_bundle = self.assemble_context(&ranked, query, context_window);
                 
                 // 6. Build response
                 let vector_count = vector_results.len();
                 let graph_count = graph_results.len();
                 
+                ctx.progress(STAGES, Some(STAGES), "Search complete");
+                
                 Ok(json!({
                     "query": query,
                     "total_results": ranked.len(),
@@ -635,7 +842,7 @@ impl SearchService for HybridSearchService {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| McpError::InvalidArguments("Missing 'query' argument".into()))?;
                 
-                let expanded = self.expand_query(query).await?;
+                let expanded = self.expand_query(query, ctx).await;
                 
                 Ok(json!({
                     "original": expanded.original,
@@ -4,19 +4,23 @@ use crate::{
     config::McpConfig,
     search::*,
     errors::{McpError, McpResult},
-    mcp::McpTool,
+    mcp::{McpTool, ToolContext},
     security::SecurityClient,
     db::Database,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
+use tracing::warn;
 
+#[derive(Clone)]
 pub struct SearchManager {
     services: HashMap<String, Arc<dyn SearchService>>,
+    /// Chunk storage, when Azure Blob is configured
+    blob: Option<Arc<BlobRetrievalService>>,
 }
 
 impl SearchManager {
-    pub async fn new(database: Database, _config: &McpConfig) -> anyhow::Result<Self> {
+    pub async fn new(database: Database, config: &McpConfig) -> anyhow::Result<Self> {
         let mut services: HashMap<String, Arc<dyn SearchService>> = HashMap::new();
         
         let _security_client = Arc::new(SecurityClient::new(database.clone()));
@@ -50,7 +54,18 @@ impl SearchManager {
         );
         services.insert("context".to_string(), Arc::new(hybrid_service));
         
-        Ok(Self { services })
+        let blob = match &config.azure_blob_connection_string {
+            Some(connection_string) => match BlobRetrievalService::from_connection_string(connection_string, &config.azure_blob_container) {
+                Ok(blob) => Some(Arc::new(blob)),
+                Err(e) => {
+                    warn!("Azure Blob storage disabled: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        
+        Ok(Self { services, blob })
     }
     
     pub fn service_count(&self) -> usize {
@@ -66,6 +81,15 @@ impl SearchManager {
         tools
     }
     
+    /// List all tools with names in the given client-facing form
+    pub fn list_all_tools_as(&self, naming: ToolNaming) -> Vec<McpTool> {
+        let mut tools = self.list_all_tools();
+        for tool in &mut tools {
+            tool.name = naming.expose(&tool.name);
+        }
+        tools
+    }
+    
     /// List all resources from all search services
     pub fn list_all_resources(&self) -> Vec<ResourceDescriptor> {
         let mut resources = Vec::new();
@@ -75,25 +99,136 @@ impl SearchManager {
         resources
     }
     
-    /// Call a tool - routes to appropriate search service based on prefix
-    /// Tool names are: "service.tool_name" (e.g. "embeddings.search")
-    pub async fn call_tool(&self, fully_qualified_name: &str, args: serde_json::Value) -> McpResult<serde_json::Value> {
-        let parts: Vec<&str> = fully_qualified_name.splitn(2, '.').collect();
-        
-        if parts.len() != 2 {
-            return Err(McpError::InvalidArguments(
-                format!("Tool name must be in format 'service.tool': {}", fully_qualified_name)
-            ));
+    /// List resource templates from all search services
+    pub fn list_all_resource_templates(&self) -> Vec<ResourceTemplate> {
+        let mut templates = Vec::new();
+        for service in self.services.values() {
+            templates.extend(service.list_resource_templates());
         }
-        
-        let (service_id, tool_name) = (parts[0], parts[1]);
+        templates
+    }
+    
+    /// Look up a tool by name in either form ("graph.search" or "graph_search")
+    pub fn get_tool(&self, name: &str) -> McpResult<McpTool> {
+        naming::split_tool_name(name)
+            .and_then(|(service_id, tool_name)| {
+                let fully_qualified_name = format!("{}.{}", service_id, tool_name);
+                self.services.get(service_id)?
+                    .list_tools()
+                    .into_iter()
+                    .find(|t| t.name == fully_qualified_name)
+            })
+            .ok_or_else(|| McpError::ToolNotFound(format!("Tool not found: {}", name)))
+    }
+    
+    /// Call a tool - routes to appropriate search service based on prefix
+    /// Tool names are: "service.tool_name" (e.g. "embeddings.search"), or
+    /// "service_tool_name" for clients using underscore naming
+    pub async fn call_tool(&self, name: &str, args: serde_json::Value, ctx: &ToolContext) -> McpResult<serde_json::Value> {
+        let (service_id, tool_name) = naming::split_tool_name(name)
+            .ok_or_else(|| McpError::InvalidArguments(
+                format!("Tool name must be in format 'service.tool' or 'service_tool': {}", name)
+            ))?;
+        let fully_qualified_name = format!("{}.{}", service_id, tool_name);
         
         let service = self.services.get(service_id)
             .ok_or_else(|| McpError::ToolNotFound(
                 format!("Search service not found: {}", service_id)
             ))?;
         
-        service.call_tool(tool_name, args).await
+        let tool = service.list_tools()
+            .into_iter()
+            .find(|t| t.name == fully_qualified_name)
+            .ok_or_else(|| McpError::ToolNotFound(
+                format!("Tool not found: {}", name)
+            ))?;
+        
+        // The input schema is the source of truth for argument types and defaults
+        let mut args = args;
+        if let Some(schema) = &tool.input_schema {
+            validation::validate_arguments(schema, &mut args)
+                .map_err(|violations| McpError::ArgumentViolations {
+                    tool: name.to_string(),
+                    violations,
+                })?;
+        }
+        
+        // Scope searches to the client's workspace unless the caller chose repos explicitly
+        let accepts_repos = tool.input_schema
+            .as_ref()
+            .and_then(|schema| schema.pointer("/properties/repos"))
+            .is_some();
+        if let Some(object) = args.as_object_mut().filter(|_| accepts_repos) {
+            let repos = ctx.root_repos();
+            if !object.contains_key("repos") && !repos.is_empty() {
+                object.insert("repos".to_string(), serde_json::json!(repos));
+            }
+        }
+        
+        let result = service.call_tool(tool_name, args, ctx).await?;
+        
+        // Every transport returns the shape the output schema declares
+        match &tool.output_schema {
+            Some(schema) => validation::conform_result(schema, result)
+                .map_err(|found| McpError::Internal(format!(
+                    "Tool {} returned {}, which does not match its output schema", name, found
+                ))),
+            None => Ok(result),
+        }
+    }
+    
+    /// Suggest values for an argument from every service that knows it.
+    ///
+    /// Returns the sorted, de-duplicated values starting with `prefix`
+    /// (case-insensitive). Services that fail are skipped.
+    pub async fn complete_argument(&self, argument: &str, prefix: &str) -> Vec<String> {
+        let results = futures::future::join_all(
+            self.services.values().map(|service| service.complete_argument(argument, prefix))
+        ).await;
+        
+        let prefix = prefix.to_lowercase();
+        let mut values = BTreeSet::new();
+        for result in results {
+            match result {
+                Ok(suggestions) => values.extend(
+                    suggestions.into_iter().filter(|v| v.to_lowercase().starts_with(&prefix))
+                ),
+                Err(e) => warn!("Completion for '{}' failed: {}", argument, e),
+            }
+        }
+        values.into_iter().collect()
+    }
+    
+    /// Names of the backends the services depend on
+    /// (`blob`, `decision_engine`, `embeddings`, `graph`)
+    pub fn backends(&self) -> Vec<&'static str> {
+        let mut backends: Vec<&'static str> = self.services.values()
+            .filter_map(|service| service.backend())
+            .chain(std::iter::once("blob"))
+            .collect();
+        backends.sort();
+        backends
+    }
+    
+    /// Check every backend the services depend on, keyed by backend name
+    pub async fn check_backends(&self) -> Vec<(&'static str, McpResult<()>)> {
+        let services = futures::future::join_all(
+            self.services.values().filter_map(|service| {
+                let backend = service.backend()?;
+                Some(async move { (backend, service.health_check().await) })
+            })
+        );
+        let blob = async {
+            match &self.blob {
+                Some(blob) => blob.health_check().await.map_err(McpError::Other),
+                None => Err(McpError::ConnectorDisabled("AZURE_BLOB_CONNECTION_STRING is not set".to_string())),
+            }
+        };
+        let (mut checks, blob) = tokio::join!(services, blob);
+        checks.push(("blob", blob));
+        
+        checks.sort_by_key(|(backend, _)| *backend);
+        checks
     }
     
     /// Read a resource - routes based on URI prefix
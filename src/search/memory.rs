@@ -6,7 +6,7 @@
 use crate::{
     search::*,
     errors::{McpError, McpResult},
-    mcp::McpTool,
+    mcp::{McpTool, ToolAnnotations, ToolContext},
 };
 use async_trait::async_trait;
 use serde::Serialize;
@@ -112,6 +112,61 @@ impl MemoryService {
         
         Ok(result)
     }
+    
+    /// List repository names known to the knowledge layer
+    async fn call_list_repos(&self) -> McpResult<Vec<String>> {
+        let url = format!("{}/api/memory/repos", self.decision_engine_url);
+        
+        debug!("📚 Listing repos: {}", url);
+        
+        let response = self.http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| McpError::Other(e.into()))?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(McpError::Other(anyhow::anyhow!(
+                "Repo listing failed with status {}: {}", status, error_text
+            )));
+        }
+        
+        let result: Value = response.json().await
+            .map_err(|e| McpError::Other(e.into()))?;
+        
+        // Either a bare list or `{ "repos": [...] }`
+        let repos = result.get("repos").unwrap_or(&result);
+        Ok(repos.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect())
+    }
+}
+
+/// Schema for one context block (a code chunk, doc section, episode or fact) with its provenance
+fn context_block_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "source_type": { "type": "string" },
+            "content": { "type": "string" },
+            "score": { "type": "number" },
+            "tokens": { "type": "integer" },
+            "timestamp": { "type": ["string", "null"], "format": "date-time" },
+            "provenance": {
+                "type": "object",
+                "properties": {
+                    "source": { "type": "string" },
+                    "path": { "type": ["string", "null"] },
+                    "url": { "type": ["string", "null"] }
+                }
+            }
+        }
+    })
 }
 
 #[async_trait]
@@ -143,7 +198,7 @@ impl super::service_trait::SearchService for MemoryService {
                         "repos": {
                             "type": "array",
                             "items": { "type": "string" },
-                            "description": "Optional: Filter by repository names"
+                            "description": "Optional: Filter by repository names (default: the client's workspace roots; [] searches everything)"
                         },
                         "time_range": {
                             "type": "object",
@@ -155,7 +210,8 @@ impl super::service_trait::SearchService for MemoryService {
                         },
                         "max_blocks": {
                             "type": "integer",
-                            "description": "Maximum number of context blocks to return (default: 20)"
+                            "description": "Maximum number of context blocks to return (default: 20)",
+                            "default": 20
                         },
                         "strategy": {
                             "type": "string",
@@ -165,6 +221,20 @@ impl super::service_trait::SearchService for MemoryService {
                     },
                     "required": ["query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "description": "Ranked context blocks with provenance, as returned by the decision engine",
+                    "properties": {
+                        "blocks": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        },
+                        "strategy_used": { "type": "string" },
+                        "total_tokens": { "type": "integer" },
+                        "query_kind": { "type": "string" }
+                    }
+                })),
+                annotations: Some(ToolAnnotations::read_only("Search Knowledge Layer")),
             },
             
             // Robot memory search
@@ -198,19 +268,42 @@ impl super::service_trait::SearchService for MemoryService {
                         },
                         "include_episodic": {
                             "type": "boolean",
-                            "description": "Include episodic memory (default: true)"
+                            "description": "Include episodic memory (default: true)",
+                            "default": true
                         },
                         "include_semantic": {
                             "type": "boolean",
-                            "description": "Include semantic facts (default: true)"
+                            "description": "Include semantic facts (default: true)",
+                            "default": true
                         },
                         "max_blocks": {
                             "type": "integer",
-                            "description": "Maximum blocks to return (default: 20)"
+                            "description": "Maximum blocks to return (default: 20)",
+                            "default": 20
                         }
                     },
                     "required": ["robot_id", "query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "description": "Episodes, observations and semantic facts from robot memory",
+                    "properties": {
+                        "robot_id": { "type": "string" },
+                        "episodes": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        },
+                        "observations": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        },
+                        "facts": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        }
+                    }
+                })),
+                annotations: Some(ToolAnnotations::read_only("Search Robot Memory")),
             },
             
             // Robot context snapshot
@@ -229,6 +322,31 @@ impl super::service_trait::SearchService for MemoryService {
                     },
                     "required": ["robot_id"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "description": "Latest robot context snapshot",
+                    "properties": {
+                        "robot_id": { "type": "string" },
+                        "captured_at": { "type": "string", "format": "date-time" },
+                        "state": {
+                            "type": "object",
+                            "description": "Current robot state (pose, task, battery, ...)"
+                        },
+                        "recent_episodes": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        },
+                        "facts": {
+                            "type": "array",
+                            "items": context_block_schema()
+                        },
+                        "active_streams": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        }
+                    }
+                })),
+                annotations: Some(ToolAnnotations::read_only("Robot Context Snapshot")),
             },
             
             // Store passive context (for future use)
@@ -259,6 +377,28 @@ impl super::service_trait::SearchService for MemoryService {
                     },
                     "required": ["content"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "success": { "type": "boolean" },
+                        "message": { "type": "string" },
+                        "id": { "type": "string" },
+                        "content_length": { "type": "integer" },
+                        "tags": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "source": { "type": ["string", "null"] }
+                    },
+                    "required": ["success", "id"]
+                })),
+                annotations: Some(ToolAnnotations {
+                    title: Some("Store Memory".to_string()),
+                    read_only_hint: Some(false),
+                    destructive_hint: Some(false),
+                    idempotent_hint: Some(false),
+                    open_world_hint: Some(false),
+                }),
             },
             
             // Analyze query (for debugging/transparency)
@@ -277,11 +417,36 @@ impl super::service_trait::SearchService for MemoryService {
                     },
                     "required": ["query"]
                 })),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "analysis": {
+                            "type": "object",
+                            "properties": {
+                                "query_kind": { "type": "string" },
+                                "modality_hint": { "type": "string" },
+                                "suggested_strategy": { "type": "string" },
+                                "confidence": { "type": "number" }
+                            }
+                        }
+                    },
+                    "required": ["query", "analysis"]
+                })),
+                annotations: Some(ToolAnnotations::read_only("Analyze Query")),
             },
         ]
     }
     
-    async fn call_tool(&self, tool: &str, args: Value) -> McpResult<Value> {
+    fn backend(&self) -> Option<&'static str> {
+        Some("decision_engine")
+    }
+    
+    async fn health_check(&self) -> McpResult<()> {
+        super::service_trait::check_health(&self.http_client, &format!("{}/health", self.decision_engine_url)).await
+    }
+    
+    async fn call_tool(&self, tool: &str, args: Value, _ctx: &ToolContext) -> McpResult<Value> {
         info!("🔧 Memory tool call: {}", tool);
         
         match tool {
@@ -476,7 +641,39 @@ impl super::service_trait::SearchService for MemoryService {
         ]
     }
     
+    fn list_resource_templates(&self) -> Vec<ResourceTemplate> {
+        vec![
+            ResourceTemplate {
+                uri_template: "memory://robot/{robot_id}/context".to_string(),
+                name: "Robot context snapshot".to_string(),
+                description: Some("Latest context snapshot for a robot: state, recent episodes, facts and active streams".to_string()),
+                mime_type: Some("application/json".to_string()),
+            },
+        ]
+    }
+    
+    async fn complete_argument(&self, argument: &str, _prefix: &str) -> McpResult<Vec<String>> {
+        match argument {
+            "repos" => self.call_list_repos().await,
+            "sources" => Ok(SOURCE_TYPES.iter().map(|s| s.to_string()).collect()),
+            _ => Ok(vec![]),
+        }
+    }
+    
     async fn read_resource(&self, uri: &str) -> McpResult<ResourceContent> {
+        // memory://robot/{robot_id}/context
+        if let Some(robot_id) = uri.strip_prefix("memory://robot/").and_then(|rest| rest.strip_suffix("/context")) {
+            if robot_id.is_empty() || robot_id.contains('/') {
+                return Err(McpError::InvalidArguments(format!("Invalid robot context URI: {}", uri)));
+            }
+            
+            let context = self.call_robot_context(robot_id).await?;
+            return Ok(ResourceContent {
+                content: context.to_string(),
+                mime_type: Some("application/json".to_string()),
+            });
+        }
+        
         if uri == "memory://knowledge-layer" {
             Ok(ResourceContent {
                 content: json!({
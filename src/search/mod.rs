@@ -8,9 +8,12 @@ pub mod memory;
 pub mod schema;
 pub mod service_trait;
 pub mod manager;
+pub mod naming;
+pub mod validation;
 
 pub use service_trait::SearchService;
 pub use manager::SearchManager;
+pub use naming::ToolNaming;
 pub use schema::*;
 
 // Re-export all search services
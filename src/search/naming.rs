@@ -0,0 +1,66 @@
+// Tool Naming - Client-facing tool name forms
+//
+// Tools are registered as `service.tool` (e.g. `context.search`). Several agent
+// clients only accept names matching `^[a-zA-Z0-9_-]{1,64}$`, so sessions can
+// see them as `service_tool` (e.g. `context_search`) instead. Service ids never
+// contain underscores, which keeps the underscore form unambiguous.
+use serde::{Deserialize, Serialize};
+
+/// How tool names are presented to a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolNaming {
+    /// `context.search` (canonical)
+    #[default]
+    Dotted,
+    /// `context_search`
+    Underscore,
+}
+
+/// Client name fragments (lowercased) of agents that reject dotted tool names
+const UNDERSCORE_CLIENTS: &[&str] = &["claude", "openai", "chatgpt", "gemini", "copilot"];
+
+impl ToolNaming {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToolNaming::Dotted => "dotted",
+            ToolNaming::Underscore => "underscore",
+        }
+    }
+
+    pub fn parse(naming: &str) -> Option<Self> {
+        match naming {
+            "dotted" => Some(ToolNaming::Dotted),
+            "underscore" => Some(ToolNaming::Underscore),
+            _ => None,
+        }
+    }
+
+    /// Pick a naming form from the `clientInfo.name` sent during `initialize`
+    pub fn detect(client_name: &str) -> Self {
+        let client_name = client_name.to_lowercase();
+        if UNDERSCORE_CLIENTS.iter().any(|fragment| client_name.contains(fragment)) {
+            ToolNaming::Underscore
+        } else {
+            ToolNaming::Dotted
+        }
+    }
+
+    /// Present a canonical `service.tool` name in this form
+    pub fn expose(&self, canonical: &str) -> String {
+        match self {
+            ToolNaming::Dotted => canonical.to_string(),
+            ToolNaming::Underscore => canonical.replacen('.', "_", 1),
+        }
+    }
+}
+
+/// Split a tool name in either form into `(service_id, tool_name)`
+pub fn split_tool_name(name: &str) -> Option<(&str, &str)> {
+    let separator = name.find(['.', '_'])?;
+    let (service_id, tool_name) = (&name[..separator], &name[separator + 1..]);
+    if service_id.is_empty() || tool_name.is_empty() {
+        return None;
+    }
+    Some((service_id, tool_name))
+}
@@ -1,6 +1,10 @@
 // Context Schema - Token-efficient, normalized data structures
 use serde::{Deserialize, Serialize};
 
+/// Source types the knowledge layer indexes, as the graph and decision engine
+/// spell them in `source_types` / `sources` filters
+pub const SOURCE_TYPES: &[&str] = &["code", "docs", "chat", "tickets"];
+
 /// Repository descriptor - normalized across GitHub, GitLab, Bitbucket
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryDescriptor {
@@ -76,6 +80,16 @@ pub struct ResourceDescriptor {
     pub uri: String,
 }
 
+/// Resource template descriptor (RFC 6570 URI template) for parameterized MCP resources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,     // "graph://entity/{id}"
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+}
+
 /// Resource content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceContent {
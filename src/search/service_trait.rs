@@ -1,7 +1,11 @@
 // Search Service Trait - Common interface for all search and retrieval services
-use crate::{search::*, mcp::McpTool, errors::McpResult};
+use crate::{search::*, mcp::{McpTool, ToolContext}, errors::{McpError, McpResult}};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::time::Duration;
+
+/// Upper bound for a backend health probe, so one hung backend can't stall the others
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[async_trait]
 pub trait SearchService: Send + Sync {
@@ -11,14 +15,36 @@ pub trait SearchService: Send + Sync {
     /// List all tools this service exposes
     fn list_tools(&self) -> Vec<McpTool>;
     
-    /// Call a tool with arguments
-    async fn call_tool(&self, tool: &str, args: Value) -> McpResult<Value>;
+    /// Call a tool with arguments. `ctx` lets long-running tools report progress to the caller.
+    async fn call_tool(&self, tool: &str, args: Value, ctx: &ToolContext) -> McpResult<Value>;
     
     /// Optional: List resources (for browsable services)
     fn list_resources(&self) -> Vec<ResourceDescriptor> {
         vec![]
     }
     
+    /// Optional: List URI templates for parameterized resources
+    fn list_resource_templates(&self) -> Vec<ResourceTemplate> {
+        vec![]
+    }
+    
+    /// Optional: Suggest values for an argument, keyed by argument name (e.g. `entity_id`).
+    /// Suggestions may ignore `prefix`; the manager filters and merges them.
+    async fn complete_argument(&self, _argument: &str, _prefix: &str) -> McpResult<Vec<String>> {
+        Ok(vec![])
+    }
+    
+    /// Optional: Name of the backend this service depends on (`embeddings`,
+    /// `graph`, `decision_engine`), reported by gRPC health checks
+    fn backend(&self) -> Option<&'static str> {
+        None
+    }
+    
+    /// Optional: Check that the backend is reachable
+    async fn health_check(&self) -> McpResult<()> {
+        Ok(())
+    }
+    
     /// Optional: Read a resource by ID
     async fn read_resource(&self, _id: &str) -> McpResult<ResourceContent> {
         Err(crate::errors::McpError::ToolNotFound(
@@ -26,3 +52,18 @@ pub trait SearchService: Send + Sync {
         ))
     }
 }
+
+/// Probe a backend's HTTP health endpoint; any 2xx response counts as healthy
+pub async fn check_health(client: &reqwest::Client, url: &str) -> McpResult<()> {
+    let response = client
+        .get(url)
+        .timeout(HEALTH_CHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| McpError::Internal(format!("Health check failed: {}", e)))?;
+    
+    if !response.status().is_success() {
+        return Err(McpError::Internal(format!("Health check returned {}", response.status())));
+    }
+    Ok(())
+}
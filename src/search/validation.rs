@@ -0,0 +1,280 @@
+// Tool Argument Validation - Checks tool arguments against their declared input_schema
+//
+// Supports the JSON Schema subset our tool schemas use: `type` (single or list),
+// `properties`, `required`, `items`, `enum`, `minimum`/`maximum` and `default`.
+// Unknown keywords (`description`, `format`, ...) are ignored.
+use serde_json::{Map, Value};
+
+/// Validate `args` against `schema`, filling in declared defaults for missing properties.
+///
+/// Returns every violation found (not just the first), each prefixed with the
+/// path of the offending value, e.g. `limit: expected integer, got string`.
+pub fn validate_arguments(schema: &Value, args: &mut Value) -> Result<(), Vec<String>> {
+    // `arguments` is optional in tools/call; treat a missing value as an empty object
+    if args.is_null() {
+        *args = Value::Object(Map::new());
+    }
+
+    let mut violations = Vec::new();
+    validate(schema, args, "arguments", &mut violations);
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Shape a tool result to match its object `output_schema`.
+///
+/// Some backends return a bare list where the schema declares an object with a
+/// single array property (e.g. `{ "ontologies": [...] }`); the list is wrapped
+/// under that property. Anything else that isn't an object is rejected with
+/// its JSON type name.
+pub fn conform_result(schema: &Value, result: Value) -> Result<Value, &'static str> {
+    match result {
+        Value::Object(_) => Ok(result),
+        Value::Array(items) => {
+            let properties = schema.get("properties").and_then(|v| v.as_object());
+            match properties.map(|p| p.iter().collect::<Vec<_>>()).as_deref() {
+                Some([(name, property)]) if declares(property.get("type").unwrap_or(&Value::Null), "array") => {
+                    let mut object = Map::new();
+                    object.insert(name.to_string(), Value::Array(items));
+                    Ok(Value::Object(object))
+                }
+                _ => Err("array"),
+            }
+        }
+        other => Err(type_name(&other)),
+    }
+}
+
+fn validate(schema: &Value, value: &mut Value, path: &str, violations: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type") {
+        if !matches_type(expected, value) {
+            violations.push(format!("{}: expected {}, got {}", path, describe_type(expected), type_name(value)));
+            return;
+        }
+        // Tools read integers with `as_u64`, so normalize 10.0 to 10
+        if declares(expected, "integer") && !declares(expected, "number") {
+            if let Some(n) = value.as_f64().filter(|_| value.is_f64()) {
+                *value = Value::from(n as i64);
+            }
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|v| v.as_array()) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            violations.push(format!("{}: must be one of {}, got {}", path, allowed.join(", "), value));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|v| v.as_f64()) {
+            if n < min {
+                violations.push(format!("{}: must be >= {}, got {}", path, min, value));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(|v| v.as_f64()) {
+            if n > max {
+                violations.push(format!("{}: must be <= {}, got {}", path, max, value));
+            }
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(schema, object, path, violations),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    validate(item_schema, item, &format!("{}[{}]", path, i), violations);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_object(schema: &Value, object: &mut Map<String, Value>, path: &str, violations: &mut Vec<String>) {
+    let properties = schema.get("properties").and_then(|v| v.as_object());
+
+    if let Some(properties) = properties {
+        for (name, property_schema) in properties {
+            if !object.contains_key(name) {
+                if let Some(default) = property_schema.get("default") {
+                    object.insert(name.clone(), default.clone());
+                }
+            }
+        }
+    }
+
+    if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+        for name in required.iter().filter_map(|v| v.as_str()) {
+            if !object.contains_key(name) {
+                violations.push(format!("{}: missing required property '{}'", path, name));
+            }
+        }
+    }
+
+    if let Some(properties) = properties {
+        for (name, value) in object.iter_mut() {
+            if let Some(property_schema) = properties.get(name) {
+                // Top-level arguments are reported by name alone
+                let property_path = if path == "arguments" {
+                    name.clone()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                validate(property_schema, value, &property_path, violations);
+            }
+        }
+    }
+}
+
+fn matches_type(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(name) => is_type(name, value),
+        Value::Array(names) => names.iter().filter_map(|v| v.as_str()).any(|name| is_type(name, value)),
+        _ => true,
+    }
+}
+
+fn declares(expected: &Value, name: &str) -> bool {
+    match expected {
+        Value::String(declared) => declared == name,
+        Value::Array(names) => names.iter().any(|v| v == name),
+        _ => false,
+    }
+}
+
+fn is_type(name: &str, value: &Value) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        // JSON Schema counts 10.0 as an integer
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(" or "),
+        other => other.as_str().unwrap_or("any").to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn search_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 10 },
+                "mode": { "type": "string", "enum": ["fast", "deep"], "default": "fast" },
+                "filters": {
+                    "type": "object",
+                    "properties": {
+                        "repos": { "type": "array", "items": { "type": "string" } },
+                        "min_score": { "type": "number" }
+                    }
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    #[test]
+    fn rejects_a_numeric_string_for_an_integer() {
+        let mut args = json!({ "query": "auth", "limit": "10" });
+        let violations = validate_arguments(&search_schema(), &mut args).unwrap_err();
+        assert_eq!(violations, ["limit: expected integer, got string"]);
+    }
+
+    #[test]
+    fn reports_every_violation() {
+        let mut args = json!({ "limit": 0, "mode": "slow" });
+        let violations = validate_arguments(&search_schema(), &mut args).unwrap_err();
+        assert_eq!(violations.len(), 3, "{:?}", violations);
+        assert!(violations.contains(&"arguments: missing required property 'query'".to_string()));
+        assert!(violations.contains(&"limit: must be >= 1, got 0".to_string()));
+        assert!(violations.iter().any(|v| v.starts_with("mode: must be one of")), "{:?}", violations);
+    }
+
+    #[test]
+    fn applies_defaults_for_missing_arguments() {
+        let mut args = json!({ "query": "auth" });
+        validate_arguments(&search_schema(), &mut args).unwrap();
+        assert_eq!(args, json!({ "query": "auth", "limit": 10, "mode": "fast" }));
+
+        // A missing arguments object is treated as empty
+        let mut args = Value::Null;
+        let violations = validate_arguments(&search_schema(), &mut args).unwrap_err();
+        assert_eq!(violations, ["arguments: missing required property 'query'"]);
+    }
+
+    #[test]
+    fn normalizes_integral_floats_to_integers() {
+        let mut args = json!({ "query": "auth", "limit": 10.0 });
+        validate_arguments(&search_schema(), &mut args).unwrap();
+        assert!(args["limit"].is_u64() || args["limit"].is_i64(), "{}", args["limit"]);
+        assert_eq!(args["limit"], 10);
+
+        let mut args = json!({ "query": "auth", "limit": 10.5 });
+        let violations = validate_arguments(&search_schema(), &mut args).unwrap_err();
+        assert_eq!(violations, ["limit: expected integer, got number"]);
+    }
+
+    #[test]
+    fn wraps_bare_lists_under_the_only_array_property() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "ontologies": { "type": "array", "items": { "type": "object" } } }
+        });
+        let list = json!([{ "name": "code" }]);
+        assert_eq!(conform_result(&schema, list.clone()).unwrap(), json!({ "ontologies": list }));
+        assert_eq!(conform_result(&schema, json!({ "ontologies": [] })).unwrap(), json!({ "ontologies": [] }));
+        assert_eq!(conform_result(&schema, json!("text")), Err("string"));
+
+        let two_properties = json!({
+            "type": "object",
+            "properties": { "items": { "type": "array" }, "total": { "type": "integer" } }
+        });
+        assert_eq!(conform_result(&two_properties, json!([])), Err("array"));
+    }
+
+    #[test]
+    fn reports_nested_paths() {
+        let mut args = json!({
+            "query": "auth",
+            "filters": { "repos": ["api", 7], "min_score": "high" }
+        });
+        let mut violations = validate_arguments(&search_schema(), &mut args).unwrap_err();
+        violations.sort();
+        assert_eq!(violations, [
+            "filters.min_score: expected number, got string",
+            "filters.repos[1]: expected string, got integer",
+        ]);
+    }
+}
@@ -0,0 +1,117 @@
+// Shared setup for the gRPC integration tests
+//
+// Each test binary compiles this module on its own and uses only part of it.
+#![allow(dead_code)]
+
+use mcp_service::{
+    db::{Database, DatabaseConfig},
+    grpc_server, McpConfig, SearchManager,
+};
+use serde_json::json;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tonic::transport::{server::TcpIncoming, Channel};
+
+/// Serve the real gRPC router on an ephemeral port and connect to it.
+///
+/// Backend URLs are read from the environment, so set them before calling this.
+pub async fn start_server() -> Channel {
+    let database = Database::new(&DatabaseConfig::default()).await.unwrap();
+    let config = McpConfig::from_env().unwrap();
+    let search_manager = Arc::new(SearchManager::new(database, &config).await.unwrap());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+    tokio::spawn(grpc_server::router(search_manager).await.serve_with_incoming(incoming));
+
+    Channel::from_shared(format!("http://{}", addr)).unwrap().connect().await.unwrap()
+}
+
+/// Answers every request with 200 OK. Runs on its own thread so it outlives
+/// the runtime of whichever test starts it.
+pub fn healthy_backend() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for mut socket in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match socket.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}");
+        }
+    });
+    format!("http://{}", addr)
+}
+
+/// Answers vector search at once and graph search after a delay; anything else 404s
+pub async fn fake_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(respond(socket));
+        }
+    });
+    format!("http://{}", addr)
+}
+
+async fn respond(mut socket: TcpStream) {
+    // Read the headers and the Content-Length body
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let Ok(n) = socket.read(&mut buf).await else { return };
+        if n == 0 {
+            return;
+        }
+        request.extend_from_slice(&buf[..n]);
+        if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+    let content_length: usize = head
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|len| len.trim().parse().ok())
+        .unwrap_or(0);
+    while request.len() < header_end + content_length {
+        let Ok(n) = socket.read(&mut buf).await else { return };
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let (status, body) = if head.starts_with("post /api/v1/search ") {
+        ("200 OK", json!({ "results": [{
+            "id": "chunk-1", "title": "jwt_validator.rs", "content": "fn validate()",
+            "path": "src/auth/jwt_validator.rs", "score": 0.9
+        }] }))
+    } else if head.starts_with("post /api/search ") {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        ("200 OK", json!({ "entities": [{
+            "id": "entity-1", "name": "AuthConfig", "entity_type": "struct", "path": "src/auth/config.rs"
+        }] }))
+    } else {
+        ("404 Not Found", json!({}))
+    };
+
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
@@ -0,0 +1,89 @@
+// gRPC health checking and server reflection
+//
+// Points the embeddings backend at a fake server that answers /health and
+// leaves the others unreachable, then checks the per-backend statuses and that
+// reflection lists the services without a copy of mcp.proto.
+mod common;
+
+use std::sync::Once;
+use std::time::{Duration, Instant};
+use tonic::transport::Channel;
+use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest};
+use tonic_reflection::pb::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+static BACKENDS: Once = Once::new();
+
+async fn start_server() -> Channel {
+    // Configure the backends once, before any test reads the environment
+    BACKENDS.call_once(|| {
+        // Nothing listens on the discard port
+        let unreachable = "http://127.0.0.1:9";
+        std::env::set_var("EMBEDDINGS_SERVICE_URL", common::healthy_backend());
+        std::env::set_var("RELATION_GRAPH_URL", unreachable);
+        std::env::set_var("DECISION_ENGINE_URL", unreachable);
+        std::env::remove_var("AZURE_BLOB_CONNECTION_STRING");
+    });
+
+    common::start_server().await
+}
+
+async fn status(client: &mut HealthClient<Channel>, service: &str) -> ServingStatus {
+    let response = client
+        .check(HealthCheckRequest { service: service.to_string() })
+        .await
+        .unwrap_or_else(|e| panic!("health check for '{}' failed: {}", service, e))
+        .into_inner();
+    ServingStatus::try_from(response.status).unwrap()
+}
+
+#[tokio::test]
+async fn health_reports_the_service_and_each_backend() {
+    let mut client = HealthClient::new(start_server().await);
+
+    assert_eq!(status(&mut client, "").await, ServingStatus::Serving);
+    assert_eq!(status(&mut client, "confuse.mcp.v1.Mcp").await, ServingStatus::Serving);
+
+    // Backends start NOT_SERVING; the healthy one flips after the first check
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while status(&mut client, "embeddings").await != ServingStatus::Serving {
+        assert!(Instant::now() < deadline, "embeddings never became SERVING");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    for backend in ["graph", "decision_engine", "blob"] {
+        assert_eq!(status(&mut client, backend).await, ServingStatus::NotServing, "{}", backend);
+    }
+
+    let unknown = client
+        .check(HealthCheckRequest { service: "no.such.Service".to_string() })
+        .await
+        .unwrap_err();
+    assert_eq!(unknown.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn reflection_lists_services() {
+    let mut client = ServerReflectionClient::new(start_server().await);
+
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client
+        .server_reflection_info(futures::stream::iter([request]))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let response = responses.message().await.unwrap().expect("a reflection response");
+    let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+        panic!("unexpected reflection response: {:?}", response.message_response);
+    };
+    let services: Vec<String> = list.service.into_iter().map(|s| s.name).collect();
+    assert!(services.contains(&"confuse.mcp.v1.Mcp".to_string()), "{:?}", services);
+    assert!(services.contains(&"grpc.health.v1.Health".to_string()), "{:?}", services);
+}
+
@@ -0,0 +1,197 @@
+// gRPC transport - the Mcp service routes into SearchManager
+//
+// Serves the real router on an ephemeral port in-process and talks to it with
+// the generated client. Only `memory.analyze_query` is run to completion since
+// it needs no backend; other tools are only called to check argument handling.
+mod common;
+
+use mcp_service::proto::confuse::mcp::v1::{
+    mcp_client::McpClient, CallToolRequest, CallToolV2Request, ListResourceTemplatesRequest,
+    ListResourcesRequest, ListToolsRequest, ReadResourceRequest, ToolSchemaRequest,
+};
+use serde_json::{json, Value};
+use tonic::transport::Channel;
+use tonic::Code;
+
+async fn start_server() -> McpClient<Channel> {
+    McpClient::new(common::start_server().await)
+}
+
+fn analyze_request(tool_id: &str, parameters: &[(&str, &str)]) -> CallToolRequest {
+    CallToolRequest {
+        tool_id: tool_id.to_string(),
+        parameters: parameters.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        user_id: "test-user".to_string(),
+        session_id: "test-session".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn list_tools_filters_by_category() {
+    let mut client = start_server().await;
+
+    let all = client.list_tools(ListToolsRequest { category: None }).await.unwrap().into_inner();
+    let analyze = all.tools.iter()
+        .find(|t| t.tool_id == "memory.analyze_query")
+        .expect("memory.analyze_query is listed");
+    assert_eq!(analyze.category, "memory");
+    assert!(analyze.parameters_schema.contains_key("query"));
+    let input_schema: Value = serde_json::from_str(&analyze.input_schema_json).unwrap();
+    assert_eq!(input_schema["required"], json!(["query"]));
+    assert!(analyze.output_schema_json.is_some());
+
+    let memory = client
+        .list_tools(ListToolsRequest { category: Some("memory".to_string()) })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!memory.tools.is_empty());
+    assert!(memory.tools.len() < all.tools.len());
+    assert!(memory.tools.iter().all(|t| t.category == "memory"));
+}
+
+#[tokio::test]
+async fn call_tool_routes_into_search_manager() {
+    let mut client = start_server().await;
+
+    for tool_id in ["memory.analyze_query", "memory_analyze_query"] {
+        let response = client
+            .call_tool(analyze_request(tool_id, &[("query", "how do I rotate the signing key")]))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success, "{:?}", response.error);
+        let result: Value = serde_json::from_str(&response.result).unwrap();
+        assert_eq!(result["query"], "how do I rotate the signing key");
+        assert_eq!(result["analysis"]["query_kind"], "how_to");
+        assert!(response.metadata.contains_key("duration_ms"));
+    }
+}
+
+#[tokio::test]
+async fn legacy_parameters_are_coerced_with_the_input_schema() {
+    let mut client = start_server().await;
+
+    // A numeric-looking string stays a string where the schema says so
+    let response = client
+        .call_tool(analyze_request("memory.analyze_query", &[("query", "42")]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.success, "{:?}", response.error);
+    let result: Value = serde_json::from_str(&response.result).unwrap();
+    assert_eq!(result["query"], "42");
+
+    // Integers and comma-separated or JSON arrays pass validation; the backend
+    // may be unreachable, which is reported in the response rather than as a status
+    for source_types in ["code, docs", r#"["code","docs"]"#] {
+        let parameters = [("query", "auth"), ("limit", "5"), ("source_types", source_types), ("repos", "[]")];
+        let response = client.call_tool(analyze_request("graph.search", &parameters)).await;
+        assert!(response.is_ok(), "{:?}", response.unwrap_err());
+    }
+
+    let invalid = client
+        .call_tool(analyze_request("graph.search", &[("query", "auth"), ("limit", "ten")]))
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.code(), Code::InvalidArgument);
+    assert!(invalid.message().contains("limit"), "{}", invalid.message());
+}
+
+#[tokio::test]
+async fn call_tool_v2_takes_json_arguments() {
+    let mut client = start_server().await;
+
+    let request = |arguments_json: &str| CallToolV2Request {
+        tool_id: "memory.analyze_query".to_string(),
+        arguments_json: arguments_json.to_string(),
+        user_id: "test-user".to_string(),
+        session_id: "test-session".to_string(),
+    };
+
+    let response = client
+        .call_tool_v2(request(r#"{"query": "why does login error"}"#))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.success, "{:?}", response.error);
+    let result: Value = serde_json::from_str(&response.result_json).unwrap();
+    assert_eq!(result["analysis"]["query_kind"], "troubleshooting");
+
+    for arguments_json in ["{ not json", r#"{"query": 42}"#, ""] {
+        let status = client.call_tool_v2(request(arguments_json)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument, "{}", arguments_json);
+    }
+}
+
+#[tokio::test]
+async fn call_tool_maps_request_errors_to_status_codes() {
+    let mut client = start_server().await;
+
+    let missing = client
+        .call_tool(analyze_request("memory.no_such_tool", &[]))
+        .await
+        .unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+
+    let invalid = client
+        .call_tool(analyze_request("memory.analyze_query", &[]))
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.code(), Code::InvalidArgument);
+    assert!(invalid.message().contains("query"), "{}", invalid.message());
+}
+
+#[tokio::test]
+async fn get_tool_schema_returns_input_schema() {
+    let mut client = start_server().await;
+
+    let schema = client
+        .get_tool_schema(ToolSchemaRequest { tool_id: "memory_analyze_query".to_string() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(schema.tool_id, "memory.analyze_query");
+
+    let json_schema: Value = serde_json::from_str(&schema.json_schema).unwrap();
+    assert_eq!(json_schema["required"], json!(["query"]));
+
+    let unknown = client
+        .get_tool_schema(ToolSchemaRequest { tool_id: "nope".to_string() })
+        .await
+        .unwrap_err();
+    assert_eq!(unknown.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn resources_match_the_mcp_surface() {
+    let mut client = start_server().await;
+
+    let resources = client.list_resources(ListResourcesRequest {}).await.unwrap().into_inner().resources;
+    assert!(resources.iter().any(|r| r.uri == "memory://knowledge-layer"));
+
+    let templates = client
+        .list_resource_templates(ListResourceTemplatesRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .resource_templates;
+    assert!(templates.iter().any(|t| t.uri_template == "memory://robot/{robot_id}/context"));
+
+    let read = |uri: &str| ReadResourceRequest { uri: uri.to_string() };
+
+    let layer = client.read_resource(read("memory://knowledge-layer")).await.unwrap().into_inner();
+    assert_eq!(layer.uri, "memory://knowledge-layer");
+    assert_eq!(layer.mime_type.as_deref(), Some("application/json"));
+    let content: Value = serde_json::from_str(&layer.content).unwrap();
+    assert_eq!(content["name"], "ConHub Knowledge Layer");
+
+    let unknown = client.read_resource(read("memory://nope")).await.unwrap_err();
+    assert_eq!(unknown.code(), Code::NotFound);
+
+    for uri in ["not-a-uri", "memory://robot//context"] {
+        let invalid = client.read_resource(read(uri)).await.unwrap_err();
+        assert_eq!(invalid.code(), Code::InvalidArgument, "{}", uri);
+    }
+}
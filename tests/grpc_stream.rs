@@ -0,0 +1,69 @@
+// gRPC CallToolStream - progress and partial results ahead of the final result
+//
+// Points the hybrid service at a fake backend whose graph search is slower than
+// its vector search, then checks that `context.search` streams the vector hits
+// first, the graph hits next, and ends with exactly one result event.
+mod common;
+
+use mcp_service::proto::confuse::mcp::v1::{mcp_client::McpClient, tool_event::Event, CallToolV2Request};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn context_search_streams_vector_then_graph_hits_then_result() {
+    let backend = common::fake_backend().await;
+    // The only test in this binary, so nothing else reads the environment concurrently
+    std::env::set_var("EMBEDDINGS_SERVICE_URL", &backend);
+    std::env::set_var("RELATION_GRAPH_URL", &backend);
+
+    let mut client = McpClient::new(common::start_server().await);
+
+    let arguments = json!({ "query": "jwt auth", "expand_query": false, "include_related": false });
+    let mut stream = client
+        .call_tool_stream(CallToolV2Request {
+            tool_id: "context.search".to_string(),
+            arguments_json: arguments.to_string(),
+            user_id: "test-user".to_string(),
+            session_id: "test-session".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut events = Vec::new();
+    while let Some(event) = stream.message().await.unwrap() {
+        events.push(event.event.expect("event is set"));
+    }
+
+    let stages: Vec<&str> = events.iter()
+        .filter_map(|e| match e {
+            Event::Partial(partial) => Some(partial.stage.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stages, ["vector", "graph"]);
+
+    let progress: Vec<f64> = events.iter()
+        .filter_map(|e| match e {
+            Event::Progress(progress) => Some(progress.progress),
+            _ => None,
+        })
+        .collect();
+    assert!(!progress.is_empty());
+    assert!(progress.windows(2).all(|w| w[0] <= w[1]), "{:?}", progress);
+
+    let Some(Event::Result(result)) = events.last() else {
+        panic!("stream must end with a result: {:?}", events);
+    };
+    assert_eq!(events.iter().filter(|e| matches!(e, Event::Result(_))).count(), 1);
+    assert!(result.success, "{:?}", result.error);
+
+    let result: Value = serde_json::from_str(&result.result_json).unwrap();
+    assert_eq!(result["vector_matches"], 1);
+    assert_eq!(result["graph_matches"], 1);
+
+    let Some(Event::Partial(vector)) = events.iter().find(|e| matches!(e, Event::Partial(_))) else {
+        unreachable!();
+    };
+    let hits: Value = serde_json::from_str(&vector.result_json).unwrap();
+    assert_eq!(hits["results"][0]["id"], "chunk-1");
+}
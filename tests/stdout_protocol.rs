@@ -0,0 +1,112 @@
+// Stdio transport - stdout must carry nothing but JSON-RPC frames
+//
+// Runs the real binary with verbose logging, drives a short session that makes
+// the server log at every level (including a parse error and a failing backend
+// call), and checks that each stdout line is a protocol message while the logs
+// end up on stderr.
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+#[test]
+fn stdout_carries_only_protocol_frames() {
+    // Nothing listens on the discard port, so backend calls fail fast and log warnings
+    let unreachable = "http://127.0.0.1:9";
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mcp-service"))
+        .env("RUST_LOG", "trace")
+        .env("MCP_PORT", free_port().to_string())
+        .env("GRPC_PORT", free_port().to_string())
+        .env("EMBEDDINGS_SERVICE_URL", unreachable)
+        .env("RELATION_GRAPH_URL", unreachable)
+        .env("DECISION_ENGINE_URL", unreachable)
+        .env("OLLAMA_URL", unreachable)
+        .env_remove("LOG_DIR")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start mcp-service");
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2025-06-18" } }),
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": {
+            "name": "graph.get_entity", "arguments": { "entity_id": "missing" }
+        } }),
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "no/such/method" }),
+    ];
+
+    let mut stdin = child.stdin.take().unwrap();
+    for request in &requests {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    writeln!(stdin, "{{ not json").unwrap();
+    stdin.flush().unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut logs = String::new();
+        let _ = stderr.read_to_string(&mut logs);
+        logs
+    });
+
+    // Responses for ids 1-4 plus the null-id parse error
+    let mut expected: HashSet<Value> = (1..=4).map(|id| json!(id)).collect();
+    expected.insert(Value::Null);
+
+    let mut lines = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while !expected.is_empty() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Ok(line) = rx.recv_timeout(remaining) else {
+            break;
+        };
+        if let Ok(Value::Object(frame)) = serde_json::from_str::<Value>(&line) {
+            if let Some(id) = frame.get("id") {
+                expected.remove(id);
+            }
+        }
+        lines.push(line);
+    }
+
+    // Give late log lines a chance to (wrongly) show up on stdout
+    while let Ok(line) = rx.recv_timeout(Duration::from_millis(500)) {
+        lines.push(line);
+    }
+
+    drop(stdin);
+    let _ = child.kill();
+    let _ = child.wait();
+    let logs = stderr_reader.join().unwrap();
+
+    assert!(expected.is_empty(), "missing responses for {:?}; stdout was:\n{}", expected, lines.join("\n"));
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let frame: Value = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("non-JSON line on stdout ({}): {}", e, line));
+        assert_eq!(frame.get("jsonrpc"), Some(&json!("2.0")), "not a JSON-RPC frame: {}", line);
+    }
+
+    assert!(logs.contains("Starting ConHub MCP Service"), "expected logs on stderr, got:\n{}", logs);
+}